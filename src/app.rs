@@ -1,6 +1,9 @@
-use crate::game::{DepositError, Game, PickupError};
-use crate::objets::Case;
-use crate::{APP_TITLE, ROBOT_COOLDOWN};
+use crate::APP_TITLE;
+use crate::campagne::EtatCampagne;
+use crate::cli::{Cli, Mode};
+use crate::game::{DepositError, Game, PickupError, RobotError};
+use crate::objets::{Case, Direction, Ingredient, IngredientEtat};
+use crate::strategy::{DefaultStrategy, Strategy, strategie_par_defaut};
 use color_eyre::Result;
 use crossterm::event::{self, Event, KeyCode, KeyEventKind};
 use ratatui::Terminal;
@@ -11,8 +14,9 @@ use ratatui::{
     style::{Color, Style},
     widgets::{Block, Gauge, Paragraph},
 };
+use std::collections::HashMap;
 use std::io;
-use std::time::{Duration, Instant};
+use std::time::Duration;
 
 const BROWN: Color = Color::Rgb(142, 73, 26);
 
@@ -31,6 +35,40 @@ macro_rules! app_println {
     };
 }
 
+const PLAYER_COLORS: [Color; 4] = [Color::Green, Color::Magenta, Color::Cyan, Color::Yellow];
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+enum Commande {
+    Deplacer(Direction),
+    Interagir,
+    // choisir un ingrédient de l'assiette en face, puis le retirer
+    Choisir,
+    Retirer,
+}
+
+// joueur 1 : flèches + Entrée ou Espace (Tab/Retour arrière pour l'assiette),
+// joueur 2 : ZQSD + E (A/X pour l'assiette)
+fn commande_clavier(key_code: KeyCode) -> Option<(usize, Commande)> {
+    let commande = match key_code {
+        KeyCode::Up => (0, Commande::Deplacer(Direction::North)),
+        KeyCode::Down => (0, Commande::Deplacer(Direction::South)),
+        KeyCode::Left => (0, Commande::Deplacer(Direction::West)),
+        KeyCode::Right => (0, Commande::Deplacer(Direction::East)),
+        KeyCode::Enter | KeyCode::Char(' ') => (0, Commande::Interagir),
+        KeyCode::Tab => (0, Commande::Choisir),
+        KeyCode::Backspace => (0, Commande::Retirer),
+        KeyCode::Char('z') => (1, Commande::Deplacer(Direction::North)),
+        KeyCode::Char('s') => (1, Commande::Deplacer(Direction::South)),
+        KeyCode::Char('q') => (1, Commande::Deplacer(Direction::West)),
+        KeyCode::Char('d') => (1, Commande::Deplacer(Direction::East)),
+        KeyCode::Char('e') => (1, Commande::Interagir),
+        KeyCode::Char('a') => (1, Commande::Choisir),
+        KeyCode::Char('x') => (1, Commande::Retirer),
+        _ => return None,
+    };
+    Some(commande)
+}
+
 fn percent_to_color(percent: f32) -> Color {
     if percent > 0.5 {
         Color::Green
@@ -41,50 +79,311 @@ fn percent_to_color(percent: f32) -> Color {
     }
 }
 
+#[derive(Debug, PartialEq, Clone, Copy, Default)]
+pub enum ControlMode {
+    Humain,
+    #[default]
+    Robot,
+    // le chef 1 au clavier, les autres suivent la stratégie
+    Coop,
+}
+
+impl ControlMode {
+    pub fn parse(text: &str) -> Option<Self> {
+        match text {
+            "human" | "humain" => Some(ControlMode::Humain),
+            "robot" => Some(ControlMode::Robot),
+            "coop" => Some(ControlMode::Coop),
+            _ => None,
+        }
+    }
+
+    pub fn is_humain(&self, id: usize) -> bool {
+        match self {
+            ControlMode::Humain => true,
+            ControlMode::Robot => false,
+            ControlMode::Coop => id == 0,
+        }
+    }
+
+    pub fn description(&self) -> &'static str {
+        match self {
+            ControlMode::Humain => {
+                "Humain (J1 : flèches + Entrée/Espace + Tab/⌫, J2 : ZQSD + E + A/X)"
+            }
+            ControlMode::Robot => "Robot",
+            ControlMode::Coop => {
+                "Coop (chef 1 au clavier : flèches/ZQSD + Entrée/Espace/E + Tab/⌫, les autres en robot)"
+            }
+        }
+    }
+}
+
 pub struct App {
     pub right_panel_content: String,
     pub should_quit: bool,
     pub game: Game,
+    // partie de départ, rejouée par reset_game
+    pub template: Game,
+    pub seed: Option<u64>,
+    pub mode: ControlMode,
+    pub strategy: Box<dyn Strategy>,
     pub logs: Vec<String>,
+    // ingrédient choisi par chaque chef humain sur l'assiette en face
+    pub selections: HashMap<usize, usize>,
+    // options de lancement, réappliquées à chaque niveau de la campagne
+    pub cli: Cli,
+    pub campagne: Option<EtatCampagne>,
+    // les étoiles de la partie finie sont déjà comptées dans la progression
+    pub resultat_enregistre: bool,
 }
 
 impl Default for App {
     fn default() -> Self {
-        let mut map = vec![vec![".".to_string(); 20]; 15];
-
-        for x in 0..20 {
-            map[0][x] = "#".to_string();
-            map[14][x] = "#".to_string();
-        }
-        for row in map.iter_mut() {
-            row[0] = "#".to_string();
-            row[19] = "#".to_string();
-        }
-
+        let game = Game::default();
         Self {
             right_panel_content: "".to_string(),
             should_quit: false,
-            game: Game::new(),
+            template: game.clone(),
+            game,
+            seed: None,
+            mode: ControlMode::default(),
+            strategy: Box::new(DefaultStrategy::new()),
             logs: vec![
                 "Application démarrée".to_string(),
                 "Carte générée".to_string(),
             ],
+            selections: HashMap::new(),
+            cli: Cli::default(),
+            campagne: None,
+            resultat_enregistre: false,
         }
     }
 }
 
 impl App {
-    pub fn new() -> Self {
-        Self::default()
+    fn log_interaction(&mut self, id: usize, result: Result<(), RobotError>) {
+        let chef = id + 1;
+        match result {
+            Ok(()) => app_println!(self, "Chef {chef} : action réussie"),
+            Err(RobotError::Pickup(PickupError::HandsFull)) => {
+                app_println!(self, "Chef {chef} : mains pleines ! Impossible de ramasser")
+            }
+            Err(RobotError::Pickup(PickupError::AssietteEmpty)) => {
+                app_println!(self, "Chef {chef} : pas d'assiette ici ! Rien à ramasser")
+            }
+            Err(RobotError::Pickup(PickupError::TableEmpty)) => {
+                app_println!(self, "Chef {chef} : table vide ! Rien à ramasser")
+            }
+            Err(RobotError::Pickup(PickupError::CouperEmpty)) => {
+                app_println!(self, "Chef {chef} : planche vide ! Rien à couper")
+            }
+            Err(RobotError::Pickup(PickupError::PoeleEmpty)) => {
+                app_println!(self, "Chef {chef} : poêle vide ! Rien à ramasser")
+            }
+            Err(RobotError::Pickup(PickupError::RetourEmpty)) => {
+                app_println!(self, "Chef {chef} : aucune assiette sale n'est revenue")
+            }
+            Err(RobotError::Pickup(PickupError::EvierEmpty)) => {
+                app_println!(self, "Chef {chef} : évier vide ! Rien à laver")
+            }
+            Err(RobotError::Pickup(PickupError::IngredientAbsent)) => {
+                app_println!(
+                    self,
+                    "Chef {chef} : cet ingrédient n'est pas sur l'assiette"
+                )
+            }
+            Err(RobotError::Pickup(PickupError::NoTarget((pos, _)))) => {
+                app_println!(self, "Chef {chef} : impossible de ramasser à {:?}", pos)
+            }
+            Err(RobotError::Deposit(DepositError::HandsEmpty)) => {
+                app_println!(self, "Chef {chef} : mains vides ! Rien à déposer")
+            }
+            Err(RobotError::Deposit(DepositError::TableFull)) => {
+                app_println!(self, "Chef {chef} : table occupée ! Impossible de déposer")
+            }
+            Err(RobotError::Deposit(DepositError::CouperFull)) => {
+                app_println!(self, "Chef {chef} : la planche est déjà utilisée")
+            }
+            Err(RobotError::Deposit(DepositError::NotChoppable)) => {
+                app_println!(self, "Chef {chef} : ça ne se coupe pas")
+            }
+            Err(RobotError::Deposit(DepositError::PoeleFull)) => {
+                app_println!(self, "Chef {chef} : la poêle est déjà utilisée")
+            }
+            Err(RobotError::Deposit(DepositError::NotCookable)) => {
+                app_println!(self, "Chef {chef} : seule la viande crue se cuit")
+            }
+            Err(RobotError::Deposit(DepositError::NoAssiette)) => {
+                app_println!(self, "Chef {chef} : il faut d'abord une assiette ici")
+            }
+            Err(RobotError::Deposit(DepositError::AssietteSale)) => {
+                app_println!(self, "Chef {chef} : l'assiette est sale, il faut la laver")
+            }
+            Err(RobotError::Deposit(DepositError::AssietteFull)) => {
+                app_println!(self, "Chef {chef} : plus de place pour cette assiette")
+            }
+            Err(RobotError::Deposit(DepositError::NeedsAssiette)) => {
+                app_println!(self, "Chef {chef} : ça se pose sur une assiette")
+            }
+            Err(RobotError::Deposit(DepositError::AssietteNotAllowed)) => {
+                app_println!(self, "Chef {chef} : pas d'assiette ici")
+            }
+            Err(RobotError::Deposit(DepositError::NothingToDiscard)) => {
+                app_println!(self, "Chef {chef} : l'assiette est déjà vide")
+            }
+            Err(RobotError::Deposit(DepositError::PlatIncomplet)) => {
+                app_println!(
+                    self,
+                    "Chef {chef} : plat incomplet ! Il manque des ingrédients"
+                )
+            }
+            Err(RobotError::Deposit(DepositError::PlatMalEmpile)) => {
+                app_println!(
+                    self,
+                    "Chef {chef} : plat mal empilé, respectez l'ordre des couches"
+                )
+            }
+            Err(RobotError::Deposit(DepositError::PlatNonCommande)) => {
+                app_println!(self, "Chef {chef} : personne n'a commandé ce plat")
+            }
+            Err(RobotError::Deposit(DepositError::EvierFull)) => {
+                app_println!(self, "Chef {chef} : l'évier est déjà plein")
+            }
+            Err(RobotError::Deposit(DepositError::NoTarget((pos, _)))) => {
+                app_println!(self, "Chef {chef} : impossible de déposer à {:?}", pos)
+            }
+        }
+    }
+
+    pub fn new(mode: ControlMode) -> Self {
+        Self {
+            mode,
+            ..Self::default()
+        }
+    }
+
+    pub fn from_cli(cli: &Cli) -> Result<Self> {
+        // en campagne, on reprend au dernier niveau débloqué
+        let campagne = EtatCampagne::from_cli(cli)?;
+        let template = match &campagne {
+            Some(campagne) => campagne.build_game(cli)?,
+            None => cli.build_game()?,
+        };
+        let mode = match cli.mode {
+            Mode::Tui(mode) => mode,
+            Mode::Headless => ControlMode::Robot,
+        };
+        let mut app = Self {
+            game: template.clone(),
+            strategy: strategie_par_defaut(template.get_players().len()),
+            template,
+            seed: cli.seed,
+            mode,
+            cli: cli.clone(),
+            campagne,
+            ..Self::default()
+        };
+        app.game = app.new_game();
+        app.log_niveau();
+        Ok(app)
+    }
+
+    fn new_game(&self) -> Game {
+        let seed = self.seed.unwrap_or_else(rand::random);
+        self.template.clone().with_seed(seed)
     }
 
     pub fn reset_game(&mut self) {
-        self.game = Game::new();
+        self.game = self.new_game();
+        self.strategy = strategie_par_defaut(self.game.get_players().len());
         self.logs.clear();
+        self.selections.clear();
         self.should_quit = false;
+        self.resultat_enregistre = false;
         app_println!(self, "Partie réinitialisée");
     }
 
+    fn log_niveau(&mut self) {
+        if let Some(campagne) = &self.campagne {
+            let message = format!(
+                "Niveau {}/{} : {}",
+                campagne.niveau + 1,
+                campagne.campagne.get_niveaux().len(),
+                campagne.get_niveau().nom
+            );
+            self.log(message);
+        }
+    }
+
+    // change de niveau de campagne s'il est débloqué, et relance la partie
+    fn changer_niveau(&mut self, niveau: usize) {
+        let Some(campagne) = &mut self.campagne else {
+            return;
+        };
+        let precedent = campagne.niveau;
+        if niveau >= campagne.campagne.get_niveaux().len() {
+            app_println!(self, "Dernier niveau de la campagne");
+            return;
+        }
+        if !campagne.aller(niveau) {
+            app_println!(
+                self,
+                "Niveau verrouillé : gagnez plus d'étoiles pour y accéder"
+            );
+            return;
+        }
+        // un fichier du niveau a pu changer depuis le lancement : on reste où l'on est
+        match campagne.build_game(&self.cli) {
+            Ok(template) => self.template = template,
+            Err(e) => {
+                campagne.niveau = precedent;
+                app_println!(self, "Niveau impossible à charger : {e}");
+                return;
+            }
+        }
+        self.reset_game();
+        self.log_niveau();
+    }
+
+    // enregistre une seule fois les étoiles de la partie finie dans la progression
+    fn terminer_niveau(&mut self) {
+        if self.resultat_enregistre {
+            return;
+        }
+        self.resultat_enregistre = true;
+        let etoiles = self.game.get_etoiles();
+        let Some(campagne) = &mut self.campagne else {
+            return;
+        };
+        match campagne.terminer(etoiles) {
+            Ok(true) => {
+                let suivant = campagne.campagne.get_niveaux()[campagne.niveau + 1]
+                    .nom
+                    .clone();
+                app_println!(self, "Niveau suivant débloqué : {suivant}");
+            }
+            Ok(false) => {}
+            Err(e) => app_println!(self, "Sauvegarde de la progression impossible : {e}"),
+        }
+    }
+
+    // ligne de la fenêtre de fin sur la suite de la campagne
+    fn suite_campagne(&self, campagne: &EtatCampagne) -> String {
+        let niveau = campagne.get_niveau();
+        let meilleur = campagne.progression.get_etoiles(&niveau.nom);
+        if campagne.is_suivant_debloque() {
+            "N : niveau suivant".to_string()
+        } else if meilleur >= niveau.etoiles_requises {
+            "Campagne terminée !".to_string()
+        } else {
+            format!(
+                "{} étoile(s) pour débloquer la suite",
+                niveau.etoiles_requises
+            )
+        }
+    }
+
     pub fn log(&mut self, message: String) {
         self.logs.push(message);
         if self.logs.len() > 100 {
@@ -96,13 +395,13 @@ impl App {
         self.log(message.to_string());
     }
 
-    pub fn run<B: Backend>(&mut self, terminal: &mut Terminal<B>, robot: bool) -> io::Result<()> {
-        let mut next_robot = Instant::now();
+    pub fn run<B: Backend>(&mut self, terminal: &mut Terminal<B>) -> io::Result<()> {
+        let mut next_robot = self.game.now();
 
         loop {
             // Gérer les événements avec timeout
             if event::poll(Duration::from_millis(16))? {
-                let return_handle = self.handle_events(robot);
+                let return_handle = self.handle_events();
                 if let Err(e) = return_handle {
                     self.log_fmt(&format!("Erreur event: {}", e));
                 }
@@ -114,12 +413,22 @@ impl App {
 
             if !self.game.is_finished() {
                 // Vérifier si c'est le moment de faire un tick
-                let now = Instant::now();
-                if robot && next_robot < now {
-                    self.game.robot();
-                    next_robot = now + ROBOT_COOLDOWN;
+                let now = self.game.now();
+                if self.mode != ControlMode::Humain && next_robot < now {
+                    let robots = (0..self.game.get_players().len())
+                        .filter(|&id| !self.mode.is_humain(id))
+                        .collect::<Vec<_>>();
+                    for id in robots {
+                        if let Err(e) = self.game.robot(id, self.strategy.as_mut()) {
+                            app_println!(self, "Chef {} : action impossible : {:?}", id + 1, e);
+                        }
+                    }
+                    next_robot = now + self.game.get_config().robot_cooldown;
+                }
+                self.game.tick();
+                if self.game.is_finished() {
+                    self.terminer_niveau();
                 }
-                self.game.tick(now);
 
                 // Render UI
                 terminal.draw(|frame| self.draw(frame))?;
@@ -127,25 +436,104 @@ impl App {
         }
     }
 
+    // l'ingrédient choisi sur l'assiette que le chef a en face
+    fn ingredient_choisi(&self, id: usize) -> Option<Ingredient> {
+        let (_, Case::ASSIETTE(Some(assiette))) = self.game.get_facing(id) else {
+            return None;
+        };
+        let ingredients = assiette.get_ingredients();
+        if ingredients.is_empty() {
+            return None;
+        }
+        let selection = self.selections.get(&id).copied().unwrap_or_default();
+        Some(ingredients[selection % ingredients.len()])
+    }
+
     fn draw(&self, frame: &mut Frame) {
         use Constraint::{Length, Min, Percentage};
 
-        let player = self.game.get_player();
+        let now = self.game.now();
+        let chefs = self
+            .game
+            .get_players()
+            .iter()
+            .map(|player| {
+                let controle = if self.mode.is_humain(player.get_id()) {
+                    "🎮"
+                } else {
+                    "🤖"
+                };
+                let mut ligne = format!(
+                    "Chef {} {}: {} {:?} {}",
+                    player.get_id() + 1,
+                    controle,
+                    player
+                        .get_object_held()
+                        .map_or("Rien".to_string(), |objet| objet.emoji().to_string()),
+                    player.get_pos(),
+                    player.get_facing().emoji(),
+                );
+                // l'assiette en face, avec l'ingrédient choisi entre crochets
+                if self.mode.is_humain(player.get_id())
+                    && let Some(choisi) = self.ingredient_choisi(player.get_id())
+                    && let (_, Case::ASSIETTE(Some(assiette))) =
+                        self.game.get_facing(player.get_id())
+                {
+                    let mut choisi = Some(choisi);
+                    let contenu = assiette
+                        .get_ingredients()
+                        .iter()
+                        .map(|ingr| {
+                            if choisi == Some(*ingr) {
+                                choisi = None;
+                                format!("[{}]", ingr.emoji())
+                            } else {
+                                ingr.emoji().to_string()
+                            }
+                        })
+                        .collect::<String>();
+                    ligne.push_str(&format!(" 🍽️ {contenu}"));
+                }
+                ligne
+            })
+            .collect::<Vec<_>>()
+            .join("\n");
+        let niveau = self.campagne.as_ref().map_or(String::new(), |campagne| {
+            format!(
+                "Niveau {}/{} : {} (N/P pour changer)\n",
+                campagne.niveau + 1,
+                campagne.campagne.get_niveaux().len(),
+                campagne.get_niveau().nom
+            )
+        });
+        let detail = self.game.get_detail_score();
         let right_panel_content = format!(
-            "Utilisez les flèches pour vous déplacer! \nItem en main: {} \nPosition: {:?} \nDirection : {} \nAssiette: {} \nScore: {}\n",
+            "{niveau}Mode: {} \n{} \nAssiettes: {} \nEn retour: {} \nScore: {} (série {}, x{:.1})\n  {:+} recettes {:+} pourboires {:+} ordre {:+} série {:+} expirées {:+} poubelle\n",
+            self.mode.description(),
+            chefs,
             self.game
-                .get_player()
-                .get_object_held()
-                .map_or("Rien".to_string(), |ingr| ingr.emoji().to_string()),
-            player.get_pos(),
-            player.get_facing().emoji(),
-            self.game
-                .get_assiette()
+                .get_assiettes()
                 .iter()
-                .map(|ingr| ingr.emoji())
+                .map(|(_, assiette)| {
+                    assiette
+                        .get_ingredients()
+                        .iter()
+                        .map(|ingr| ingr.emoji())
+                        .collect::<String>()
+                })
+                .map(|contenu| format!("[{contenu}]"))
                 .collect::<Vec<_>>()
-                .join(", "),
+                .join(" "),
+            self.game.get_retours().len(),
             self.game.get_score(),
+            detail.get_livraisons_d_affilee(),
+            detail.multiplicateur(self.game.get_config()),
+            detail.recettes,
+            detail.pourboires,
+            detail.ordre,
+            detail.serie,
+            -detail.expirations,
+            -detail.poubelle,
         );
 
         let vertical = Layout::vertical([Length(1), Min(0), Length(5)]);
@@ -178,7 +566,6 @@ impl App {
             right_recipe_list,
         );
         let recettes = self.game.get_recettes();
-        let recette_height = 5;
         let padded_recipe_list = right_recipe_list.inner(Margin {
             vertical: 1,
             horizontal: 1,
@@ -187,25 +574,50 @@ impl App {
             Block::default().style(Style::default().bg(Color::Blue)),
             padded_recipe_list,
         );
-        let max_recettes = padded_recipe_list.height as usize / recette_height;
-        for (i, recette) in recettes.iter().take(max_recettes).enumerate() {
-            let ingredients = recette
-                .get_ingredients()
-                .iter()
-                .map(|ingr| ingr.emoji())
-                .collect::<Vec<&str>>()
-                .join(", ");
+        let mut y = padded_recipe_list.y;
+        for (i, recette) in recettes.iter().enumerate() {
+            // une recette ordonnée s'affiche en pile, la couche du haut en premier
+            let lignes = if recette.is_ordonnee() {
+                recette
+                    .get_couches()
+                    .iter()
+                    .rev()
+                    .enumerate()
+                    .map(|(etage, couche)| {
+                        let couche = couche
+                            .iter()
+                            .map(|ingr| ingr.emoji())
+                            .collect::<Vec<&str>>()
+                            .join(" ");
+                        let prefixe = if etage == 0 { "Pile :" } else { "" };
+                        format!("{prefixe:<7}│ {couche}")
+                    })
+                    .collect::<Vec<_>>()
+            } else {
+                let ingredients = recette
+                    .get_ingredients()
+                    .iter()
+                    .map(|ingr| ingr.emoji())
+                    .collect::<Vec<&str>>()
+                    .join(", ");
+                vec![format!("Ingrédients : {ingredients}")]
+            };
+            let recette_height = lignes.len() as u16 + 4;
+            if y + recette_height > padded_recipe_list.bottom() {
+                break;
+            }
 
             let recipe_box = Block::bordered()
-                .title(format!("Recette {}", i + 1))
-                .style(Style::default().bg(percent_to_color(recette.get_percent_left())));
+                .title(format!("{} : {}", i + 1, recette.get_nom()))
+                .style(Style::default().bg(percent_to_color(recette.get_percent_left(now))));
 
             let area = Rect {
                 x: padded_recipe_list.x,
-                y: padded_recipe_list.y + (i * recette_height) as u16,
+                y,
                 width: padded_recipe_list.width,
-                height: recette_height as u16,
+                height: recette_height,
             };
+            y += recette_height;
 
             frame.render_widget(recipe_box, area);
 
@@ -216,7 +628,7 @@ impl App {
                 horizontal: 1,
             });
             let recipe_paragraph =
-                Paragraph::new(format!("Ingrédients : {}\nTemps restant :", ingredients));
+                Paragraph::new(format!("{}\nTemps restant :", lignes.join("\n")));
             frame.render_widget(recipe_paragraph, para_area_padded);
 
             let gauge_area_padded = gauge_area.inner(Margin {
@@ -224,13 +636,16 @@ impl App {
                 horizontal: 1,
             });
             let gauge = Gauge::default()
-                .percent((recette.get_percent_left() * 100.) as u16)
-                .label(format!("{:.2}s", recette.get_temps_restant().as_secs_f32()))
+                .percent((recette.get_percent_left(now) * 100.) as u16)
+                .label(format!(
+                    "{:.2}s",
+                    recette.get_temps_restant(now).as_secs_f32()
+                ))
                 .style(Style::default().fg(Color::White).bg(Color::Black))
                 .gauge_style(
                     Style::default()
                         .fg(Color::White)
-                        .bg(percent_to_color(recette.get_percent_left())),
+                        .bg(percent_to_color(recette.get_percent_left(now))),
                 );
             frame.render_widget(gauge, gauge_area_padded);
         }
@@ -266,8 +681,14 @@ impl App {
                     height: cell_height,
                 };
 
-                let (style, letter) = if (x, y) == player.get_pos() {
-                    (Style::default().bg(Color::Green).fg(Color::Black), "🧑‍🍳")
+                let player = self
+                    .game
+                    .get_players()
+                    .iter()
+                    .find(|player| player.get_pos() == (x, y));
+                let (style, letter) = if let Some(player) = player {
+                    let color = PLAYER_COLORS[player.get_id() % PLAYER_COLORS.len()];
+                    (Style::default().bg(color).fg(Color::Black), "🧑‍🍳")
                 } else {
                     match cell {
                         Case::Table(None) => (Style::default().bg(BROWN).fg(Color::White), " "),
@@ -278,11 +699,50 @@ impl App {
                             Style::default().bg(Color::Red).fg(Color::White),
                             ingr.emoji(),
                         ),
-                        Case::COUPER => {
+                        Case::COUPER(None) => {
                             (Style::default().bg(Color::LightBlue).fg(Color::Black), "🔪")
                         }
-                        Case::ASSIETTE => {
-                            (Style::default().bg(Color::DarkGray).fg(Color::White), "🍽️")
+                        Case::COUPER(Some(decoupe)) => (
+                            Style::default().bg(Color::LightBlue).fg(Color::Black),
+                            decoupe.ingredient.emoji(),
+                        ),
+                        Case::ASSIETTE(None) => {
+                            (Style::default().bg(Color::DarkGray).fg(Color::White), " ")
+                        }
+                        Case::ASSIETTE(Some(assiette)) => (
+                            Style::default().bg(Color::DarkGray).fg(Color::White),
+                            assiette.emoji(),
+                        ),
+                        Case::PASSE => (Style::default().bg(Color::Yellow).fg(Color::Black), "🛎️"),
+                        Case::RETOUR(0) => {
+                            (Style::default().bg(Color::Magenta).fg(Color::White), " ")
+                        }
+                        Case::RETOUR(_) => {
+                            (Style::default().bg(Color::Magenta).fg(Color::White), "🥣")
+                        }
+                        Case::POUBELLE => {
+                            (Style::default().bg(Color::Black).fg(Color::White), "🗑️")
+                        }
+                        Case::EVIER(None) => {
+                            (Style::default().bg(Color::Cyan).fg(Color::Black), "🚰")
+                        }
+                        Case::EVIER(Some(_)) => {
+                            (Style::default().bg(Color::Cyan).fg(Color::Black), "🧽")
+                        }
+                        Case::POELE(None) => {
+                            (Style::default().bg(Color::Gray).fg(Color::Black), "🍳")
+                        }
+                        Case::POELE(Some(cuisson)) => {
+                            let progression = cuisson.progression(now, self.game.get_config());
+                            let couleur = match cuisson.ingredient.etat {
+                                IngredientEtat::Normal => percent_to_color(1. - progression),
+                                IngredientEtat::Brule => Color::Black,
+                                _ => Color::Red,
+                            };
+                            (
+                                Style::default().bg(couleur).fg(Color::White),
+                                cuisson.ingredient.emoji(),
+                            )
                         }
                         _ => (Style::default().bg(Color::White).fg(Color::White), " "),
                     }
@@ -301,6 +761,29 @@ impl App {
                     let cell_paragraph = Paragraph::new(letter).style(style);
                     frame.render_widget(cell_paragraph, text_area);
                 }
+
+                // jauge de découpe ou de lavage sur la dernière ligne de la case
+                let progression = match cell {
+                    Case::COUPER(Some(decoupe)) => {
+                        Some(decoupe.progression(self.game.get_config()))
+                    }
+                    Case::EVIER(Some(lavage)) => Some(lavage.progression(self.game.get_config())),
+                    _ => None,
+                };
+                if player.is_none()
+                    && let Some(progression) = progression
+                {
+                    let gauge_area = Rect {
+                        y: cell_area.y + cell_height.saturating_sub(1),
+                        height: 1,
+                        ..cell_area
+                    };
+                    let gauge = Gauge::default()
+                        .gauge_style(Style::default().fg(Color::Green).bg(Color::Black))
+                        .ratio(progression as f64)
+                        .label("");
+                    frame.render_widget(gauge, gauge_area);
+                }
             }
         }
 
@@ -330,7 +813,7 @@ impl App {
             use ratatui::widgets::Clear;
             let area = frame.area();
             let width = std::cmp::min(40, area.width.saturating_sub(10));
-            let height = 7u16;
+            let height = if self.campagne.is_some() { 21 } else { 18 };
             let x = area.x + (area.width.saturating_sub(width)) / 2;
             let y = area.y + (area.height.saturating_sub(height)) / 2;
             let rect = Rect {
@@ -351,15 +834,45 @@ impl App {
                 vertical: 1,
                 horizontal: 2,
             });
-            let text = Paragraph::new(
-                format!("Partie finie !\nScore final: {}\n\nAppuyez sur R pour rejouer \nou échap pour quitter.", self.game.get_score()),
-            )
+            let fin = if self.game.is_perdu() {
+                format!(
+                    "Perdu ! {} commandes expirées",
+                    self.game.get_recettes_expirees()
+                )
+            } else {
+                "Partie finie !".to_string()
+            };
+            // les étoiles gagnées pleines, chacune avec son seuil
+            let etoiles = self.game.get_etoiles();
+            let seuils = self
+                .game
+                .get_config()
+                .etoiles
+                .iter()
+                .enumerate()
+                .map(|(i, seuil)| {
+                    let etoile = if i < etoiles { "★" } else { "☆" };
+                    format!("{etoile} {seuil}")
+                })
+                .collect::<Vec<_>>()
+                .join("   ");
+            let (niveau, suite) = match &self.campagne {
+                Some(campagne) => (
+                    format!("{}\n", campagne.get_niveau().nom),
+                    format!("\n{}\n", self.suite_campagne(campagne)),
+                ),
+                None => (String::new(), String::new()),
+            };
+            let text = Paragraph::new(format!(
+                "{niveau}{fin}\nÉtoiles : {etoiles}/3\n{seuils}\n\n{}\n{suite}\nAppuyez sur R pour rejouer \nou échap pour quitter.",
+                self.game.get_detail_score()
+            ))
             .style(Style::default().fg(Color::White));
             frame.render_widget(text, inner);
         }
     }
 
-    fn handle_events(&mut self, robot: bool) -> Result<()> {
+    fn handle_events(&mut self) -> Result<()> {
         let key_event = match event::read()? {
             Event::Key(key) => key,
             _ => return Ok(()),
@@ -381,62 +894,96 @@ impl App {
             KeyCode::Char('r') => {
                 app_println!(self, "reset !!!");
                 self.reset_game();
+                self.log_niveau();
+            }
+            KeyCode::Char('n') if self.campagne.is_some() => {
+                let niveau = self.campagne.as_ref().map_or(0, |campagne| campagne.niveau);
+                self.changer_niveau(niveau + 1);
+                return Ok(());
+            }
+            KeyCode::Char('p') if self.campagne.is_some() => {
+                let niveau = self.campagne.as_ref().map_or(0, |campagne| campagne.niveau);
+                self.changer_niveau(niveau.saturating_sub(1));
+                return Ok(());
             }
             _ => {}
         }
 
-        if robot {
+        let Some((slot, commande)) = commande_clavier(key_code) else {
             return Ok(());
-        }
+        };
+        let humains = (0..self.game.get_players().len())
+            .filter(|&id| self.mode.is_humain(id))
+            .collect::<Vec<_>>();
+        // seul, un joueur peut utiliser les deux jeux de touches
+        let id = match humains.as_slice() {
+            [id] => *id,
+            _ => match humains.get(slot) {
+                Some(id) => *id,
+                None => return Ok(()),
+            },
+        };
 
-        match key_code {
-            // KeyCode::Up | KeyCode::Char('z') => {
-            //     self.game.move_player(Direction::North);
-            // }
-            // KeyCode::Down | KeyCode::Char('s') => {
-            //     self.game.move_player(Direction::South);
-            // }
-            // KeyCode::Left | KeyCode::Char('q') => {
-            //     self.game.move_player(Direction::West);
-            // }
-            // KeyCode::Right | KeyCode::Char('d') => {
-            //     self.game.move_player(Direction::East);
-            // }
-            KeyCode::Char(' ') => {
-                let result = self.game.pickup();
-                match result {
-                    Ok(()) => app_println!(self, "Objet ramassé avec succès"),
-                    Err(PickupError::HandsFull) => {
-                        app_println!(self, "Mains pleines ! Impossible de ramasser")
-                    }
-                    Err(PickupError::AssietteEmpty) => {
-                        app_println!(self, "Assiette vide ! Rien à ramasser")
-                    }
-                    Err(PickupError::TableEmpty) => {
-                        app_println!(self, "Table vide ! Rien à ramasser")
-                    }
-                    Err(PickupError::NoTarget((pos, _))) => {
-                        app_println!(self, "Impossible de ramasser à {:?}", pos)
-                    }
-                }
+        match commande {
+            Commande::Deplacer(direction) => self.game.move_player(id, direction),
+            Commande::Interagir => {
+                let result = self.game.interact(id);
+                self.log_interaction(id, result);
             }
-            KeyCode::Char('e') => {
-                let result = self.game.deposit();
-                match result {
-                    Ok(()) => app_println!(self, "Objet déposé avec succès"),
-                    Err(DepositError::HandsEmpty) => {
-                        app_println!(self, "Mains vides ! Rien à déposer")
-                    }
-                    Err(DepositError::TableFull) => {
-                        app_println!(self, "Table occupée ! Impossible de déposer")
-                    }
-                    Err(DepositError::NoTarget((pos, _))) => {
-                        app_println!(self, "Impossible de déposer à {:?}", pos)
-                    }
-                }
+            Commande::Choisir => {
+                let selection = self.selections.entry(id).or_default();
+                *selection += 1;
+            }
+            Commande::Retirer => {
+                let Some(ingredient) = self.ingredient_choisi(id) else {
+                    app_println!(self, "Chef {} : aucune assiette garnie en face", id + 1);
+                    return Ok(());
+                };
+                let result = self.game.retirer(id, ingredient);
+                self.log_interaction(id, result.map_err(RobotError::Pickup));
+                self.selections.remove(&id);
             }
-            _ => {}
         }
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::campagne::Progression;
+    use std::path::PathBuf;
+
+    #[test]
+    fn espace_et_entree_font_interagir_le_premier_joueur() {
+        for key_code in [KeyCode::Enter, KeyCode::Char(' ')] {
+            assert_eq!(commande_clavier(key_code), Some((0, Commande::Interagir)));
+        }
+        assert_eq!(
+            commande_clavier(KeyCode::Char('e')),
+            Some((1, Commande::Interagir))
+        );
+    }
+
+    #[test]
+    fn le_dernier_niveau_n_est_pas_verrouille() {
+        let mut app = App {
+            campagne: Some(EtatCampagne {
+                campagne: "[Premier]\n[Deuxième]".parse().unwrap(),
+                progression: Progression::default(),
+                chemin_progression: PathBuf::new(),
+                niveau: 0,
+            }),
+            ..App::default()
+        };
+
+        app.changer_niveau(1);
+        assert_eq!(
+            app.logs.last().unwrap(),
+            "Niveau verrouillé : gagnez plus d'étoiles pour y accéder"
+        );
+        app.changer_niveau(2);
+        assert_eq!(app.logs.last().unwrap(), "Dernier niveau de la campagne");
+        assert_eq!(app.campagne.unwrap().niveau, 0);
+    }
+}
@@ -0,0 +1,114 @@
+use color_eyre::{Result, eyre::eyre};
+use hai716i_poasma::{
+    cli::{Cli, CliError},
+    simulation::{SimulationResult, Summary, simulate},
+    strategy::strategie_par_defaut,
+};
+
+const USAGE: &str = "Usage : overcook-sim [--games N] [--details] [--chefs-max N] [OPTIONS]
+  --games N                     nombre de parties, graines consécutives depuis --seed (défaut : 100)
+  --details                     affiche le résultat de chaque partie
+  --chefs-max N                 rejoue les parties de 1 à N chefs et compare le débit
+Options communes avec le jeu :";
+
+struct Options {
+    games: u64,
+    details: bool,
+    chefs_max: Option<usize>,
+    cli: Cli,
+}
+
+fn parse_args() -> Result<Options> {
+    let mut games = 100;
+    let mut details = false;
+    let mut chefs_max = None;
+    let mut rest = Vec::new();
+
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--games" => {
+                let value = args.next().ok_or(eyre!("--games attend une valeur"))?;
+                games = value.parse()?;
+            }
+            "--details" => details = true,
+            "--chefs-max" => {
+                let value = args.next().ok_or(eyre!("--chefs-max attend une valeur"))?;
+                chefs_max = Some(value.parse()?);
+            }
+            _ => rest.push(arg),
+        }
+    }
+
+    let cli = match Cli::parse(rest) {
+        Err(CliError::Help) => {
+            println!("{USAGE}\n{}", CliError::Help);
+            std::process::exit(0);
+        }
+        cli => cli?,
+    };
+    Ok(Options {
+        games,
+        details,
+        chefs_max,
+        cli,
+    })
+}
+
+fn jouer(options: &Options, cli: &Cli) -> Result<Vec<SimulationResult>> {
+    let template = cli.build_game()?;
+    let first_seed = cli.seed.unwrap_or(0);
+
+    let mut results = Vec::new();
+    for i in 0..options.games {
+        let game = template.clone().with_seed(first_seed.wrapping_add(i));
+        let mut strategy = strategie_par_defaut(game.get_players().len());
+        let result = simulate(game, strategy.as_mut());
+        if options.details {
+            println!("{result}");
+        }
+        results.push(result);
+    }
+    Ok(results)
+}
+
+fn main() -> Result<()> {
+    color_eyre::install()?;
+    let options = parse_args()?;
+
+    let Some(chefs_max) = options.chefs_max else {
+        let results = jouer(&options, &options.cli)?;
+        match Summary::new(&results) {
+            Some(summary) => print!("{summary}"),
+            None => println!("Aucune partie jouée"),
+        }
+        return Ok(());
+    };
+
+    println!("chefs | score moyen | livrées | recettes/min | expirées");
+    for chefs in 1..=chefs_max {
+        let cli = Cli {
+            chefs: Some(chefs),
+            ..options.cli.clone()
+        };
+        // la carte n'a peut-être pas de place pour autant de chefs : on passe à la ligne suivante
+        let results = match jouer(&options, &cli) {
+            Ok(results) => results,
+            Err(e) => {
+                println!("{chefs:>5} | non supporté : {e}");
+                continue;
+            }
+        };
+        if let Some(summary) = Summary::new(&results) {
+            println!(
+                "{:>5} | {:>11.2} | {:>7.2} | {:>12.2} | {:>8.2}",
+                chefs,
+                summary.score_moyen,
+                summary.livrees_moyen,
+                summary.debit,
+                summary.expirees_moyen
+            );
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,430 @@
+use std::{
+    collections::BTreeMap,
+    fs, io,
+    path::{Path, PathBuf},
+    str::FromStr,
+};
+
+use color_eyre::{Result, eyre::WrapErr};
+
+use crate::{cli::Cli, game::Game};
+
+pub const PROGRESSION_PAR_DEFAUT: &str = "progression.txt";
+
+#[derive(Debug)]
+pub enum CampagneError {
+    Io(io::Error),
+    Syntax { line: usize },
+    UnknownKey { line: usize, key: String },
+    InvalidValue { line: usize, key: String },
+    Vide,
+}
+
+impl From<io::Error> for CampagneError {
+    fn from(error: io::Error) -> Self {
+        CampagneError::Io(error)
+    }
+}
+
+impl std::fmt::Display for CampagneError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CampagneError::Io(error) => write!(f, "lecture de la campagne impossible : {error}"),
+            CampagneError::Syntax { line } => {
+                write!(f, "ligne {line} : `[Nom]` ou `cle = valeur` attendu")
+            }
+            CampagneError::UnknownKey { line, key } => {
+                write!(f, "ligne {line} : clé inconnue {key}")
+            }
+            CampagneError::InvalidValue { line, key } => {
+                write!(f, "ligne {line} : valeur invalide pour {key}")
+            }
+            CampagneError::Vide => write!(f, "la campagne ne contient aucun niveau"),
+        }
+    }
+}
+
+impl std::error::Error for CampagneError {}
+
+// Un niveau : sa carte, son livre de recettes et ses réglages, par défaut ceux du jeu
+#[derive(Debug, PartialEq, Clone)]
+pub struct Niveau {
+    pub nom: String,
+    pub carte: Option<PathBuf>,
+    pub recettes: Option<PathBuf>,
+    pub config: Option<PathBuf>,
+    // étoiles à obtenir ici pour débloquer le niveau suivant
+    pub etoiles_requises: usize,
+}
+
+impl Niveau {
+    fn new(nom: String) -> Self {
+        Self {
+            nom,
+            carte: None,
+            recettes: None,
+            config: None,
+            etoiles_requises: 1,
+        }
+    }
+
+    // les fichiers du niveau remplacent ceux de la ligne de commande, les autres options s'appliquent
+    pub fn build_game(&self, cli: &Cli) -> Result<Game> {
+        let cli = Cli {
+            map: self.carte.clone().or_else(|| cli.map.clone()),
+            recettes: self.recettes.clone().or_else(|| cli.recettes.clone()),
+            config: self.config.clone().or_else(|| cli.config.clone()),
+            ..cli.clone()
+        };
+        cli.build_game()
+    }
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct Campagne {
+    niveaux: Vec<Niveau>,
+}
+
+impl FromStr for Campagne {
+    type Err = CampagneError;
+
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        let mut niveaux: Vec<Niveau> = Vec::new();
+
+        for (i, line) in text.lines().enumerate() {
+            let line_number = i + 1;
+            let line = line.split('#').next().unwrap_or_default().trim();
+            if line.is_empty() {
+                continue;
+            }
+
+            if let Some(nom) = line.strip_prefix('[').and_then(|l| l.strip_suffix(']')) {
+                let nom = nom.trim();
+                if nom.is_empty() || nom.contains('=') {
+                    return Err(CampagneError::Syntax { line: line_number });
+                }
+                niveaux.push(Niveau::new(nom.to_string()));
+                continue;
+            }
+
+            let (key, value) = line
+                .split_once('=')
+                .ok_or(CampagneError::Syntax { line: line_number })?;
+            let (key, value) = (key.trim(), value.trim());
+            let invalid = || CampagneError::InvalidValue {
+                line: line_number,
+                key: key.to_string(),
+            };
+            let niveau = niveaux
+                .last_mut()
+                .ok_or(CampagneError::Syntax { line: line_number })?;
+
+            match key {
+                "carte" => niveau.carte = Some(PathBuf::from(value)),
+                "recettes" => niveau.recettes = Some(PathBuf::from(value)),
+                "config" => niveau.config = Some(PathBuf::from(value)),
+                "etoiles_requises" => {
+                    niveau.etoiles_requises = value
+                        .parse()
+                        .ok()
+                        .filter(|&etoiles| etoiles <= 3)
+                        .ok_or_else(invalid)?
+                }
+                _ => {
+                    return Err(CampagneError::UnknownKey {
+                        line: line_number,
+                        key: key.to_string(),
+                    });
+                }
+            }
+        }
+
+        if niveaux.is_empty() {
+            return Err(CampagneError::Vide);
+        }
+        Ok(Self { niveaux })
+    }
+}
+
+impl Campagne {
+    // les chemins des niveaux sont relatifs au fichier de la campagne
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self, CampagneError> {
+        let text = fs::read_to_string(&path)?;
+        let mut campagne = Self::from_str(&text)?;
+        let dossier = path.as_ref().parent().unwrap_or(Path::new(""));
+        for niveau in &mut campagne.niveaux {
+            for chemin in [&mut niveau.carte, &mut niveau.recettes, &mut niveau.config]
+                .into_iter()
+                .flatten()
+            {
+                *chemin = dossier.join(&chemin);
+            }
+        }
+        Ok(campagne)
+    }
+
+    pub fn get_niveaux(&self) -> &Vec<Niveau> {
+        &self.niveaux
+    }
+
+    // le premier niveau est toujours ouvert, chacun des suivants demande assez d'étoiles au précédent
+    pub fn is_debloque(&self, progression: &Progression, niveau: usize) -> bool {
+        niveau < self.niveaux.len()
+            && self.niveaux[..niveau].iter().all(|precedent| {
+                progression.get_etoiles(&precedent.nom) >= precedent.etoiles_requises
+            })
+    }
+
+    // on reprend au dernier niveau débloqué
+    pub fn reprise(&self, progression: &Progression) -> usize {
+        (0..self.niveaux.len())
+            .take_while(|&niveau| self.is_debloque(progression, niveau))
+            .last()
+            .unwrap_or_default()
+    }
+}
+
+// Meilleures étoiles obtenues à chaque niveau, sauvegardées en `Nom du niveau = étoiles`
+#[derive(Debug, Default, PartialEq, Clone)]
+pub struct Progression {
+    etoiles: BTreeMap<String, usize>,
+}
+
+impl Progression {
+    // sans fichier, la campagne commence
+    pub fn charger(path: impl AsRef<Path>) -> Result<Self, CampagneError> {
+        let text = match fs::read_to_string(path) {
+            Ok(text) => text,
+            Err(error) if error.kind() == io::ErrorKind::NotFound => return Ok(Self::default()),
+            Err(error) => return Err(error.into()),
+        };
+
+        let mut progression = Self::default();
+        for (i, line) in text.lines().enumerate() {
+            let line_number = i + 1;
+            let line = line.split('#').next().unwrap_or_default().trim();
+            if line.is_empty() {
+                continue;
+            }
+            let (nom, etoiles) = line
+                .split_once('=')
+                .ok_or(CampagneError::Syntax { line: line_number })?;
+            let etoiles = etoiles
+                .trim()
+                .parse()
+                .map_err(|_| CampagneError::InvalidValue {
+                    line: line_number,
+                    key: nom.trim().to_string(),
+                })?;
+            progression.etoiles.insert(nom.trim().to_string(), etoiles);
+        }
+        Ok(progression)
+    }
+
+    pub fn sauvegarder(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        let mut text = "# Meilleures étoiles obtenues à chaque niveau de la campagne\n".to_string();
+        for (nom, etoiles) in &self.etoiles {
+            text.push_str(&format!("{nom} = {etoiles}\n"));
+        }
+        fs::write(path, text)
+    }
+
+    pub fn get_etoiles(&self, nom: &str) -> usize {
+        self.etoiles.get(nom).copied().unwrap_or_default()
+    }
+
+    // ne garde que le meilleur résultat, vrai s'il s'est amélioré
+    pub fn enregistrer(&mut self, nom: &str, etoiles: usize) -> bool {
+        if etoiles <= self.get_etoiles(nom) {
+            return false;
+        }
+        self.etoiles.insert(nom.to_string(), etoiles);
+        true
+    }
+}
+
+// La campagne en cours : ses niveaux, la progression du joueur et le niveau joué
+#[derive(Debug, PartialEq, Clone)]
+pub struct EtatCampagne {
+    pub campagne: Campagne,
+    pub progression: Progression,
+    pub chemin_progression: PathBuf,
+    pub niveau: usize,
+}
+
+impl EtatCampagne {
+    pub fn from_cli(cli: &Cli) -> Result<Option<Self>> {
+        let Some(path) = &cli.campagne else {
+            return Ok(None);
+        };
+        let campagne = Campagne::from_file(path)?;
+        // une campagne dont un niveau ne se charge pas est refusée dès le lancement
+        for niveau in campagne.get_niveaux() {
+            niveau
+                .build_game(cli)
+                .wrap_err_with(|| format!("niveau {}", niveau.nom))?;
+        }
+        let chemin_progression = cli
+            .progression
+            .clone()
+            .unwrap_or_else(|| PathBuf::from(PROGRESSION_PAR_DEFAUT));
+        let progression = Progression::charger(&chemin_progression)?;
+        let niveau = campagne.reprise(&progression);
+        Ok(Some(Self {
+            campagne,
+            progression,
+            chemin_progression,
+            niveau,
+        }))
+    }
+
+    pub fn get_niveau(&self) -> &Niveau {
+        &self.campagne.get_niveaux()[self.niveau]
+    }
+
+    pub fn build_game(&self, cli: &Cli) -> Result<Game> {
+        self.get_niveau().build_game(cli)
+    }
+
+    // passe à un autre niveau s'il est débloqué
+    pub fn aller(&mut self, niveau: usize) -> bool {
+        if !self.campagne.is_debloque(&self.progression, niveau) {
+            return false;
+        }
+        self.niveau = niveau;
+        true
+    }
+
+    pub fn is_suivant_debloque(&self) -> bool {
+        self.campagne
+            .is_debloque(&self.progression, self.niveau + 1)
+    }
+
+    // étoiles du niveau joué, sauvegardées si c'est un record ; vrai si cela débloque le suivant
+    pub fn terminer(&mut self, etoiles: usize) -> io::Result<bool> {
+        let deja_debloque = self.is_suivant_debloque();
+        let nom = self.get_niveau().nom.clone();
+        if self.progression.enregistrer(&nom, etoiles) {
+            self.progression.sauvegarder(&self.chemin_progression)?;
+        }
+        Ok(!deja_debloque && self.is_suivant_debloque())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CAMPAGNE: &str = "[Premier]\ncarte = cartes/premier.txt\n\n[Deuxième]\netoiles_requises = 2 # pour ouvrir le troisième\n\n[Troisième]\nrecettes = livre.txt\nconfig = difficile.toml";
+
+    fn temporaire(nom: &str) -> PathBuf {
+        std::env::temp_dir().join(format!("{nom}-{}.txt", std::process::id()))
+    }
+
+    #[test]
+    fn la_campagne_fournie_se_lit() {
+        Campagne::from_file("campagnes/default.txt").unwrap();
+    }
+
+    #[test]
+    fn les_niveaux_gardent_les_reglages_du_jeu_par_defaut() {
+        let campagne = Campagne::from_str(CAMPAGNE).unwrap();
+        let [premier, deuxieme, troisieme] = campagne.get_niveaux().as_slice() else {
+            panic!("trois niveaux attendus");
+        };
+        assert_eq!(premier.carte, Some(PathBuf::from("cartes/premier.txt")));
+        assert_eq!(premier.recettes, None);
+        assert_eq!(premier.etoiles_requises, 1);
+        assert_eq!(deuxieme.nom, "Deuxième");
+        assert_eq!(deuxieme.etoiles_requises, 2);
+        assert_eq!(troisieme.config, Some(PathBuf::from("difficile.toml")));
+    }
+
+    #[test]
+    fn les_erreurs_donnent_leur_ligne() {
+        assert!(matches!(
+            Campagne::from_str("carte = premier.txt"),
+            Err(CampagneError::Syntax { line: 1 })
+        ));
+        assert!(matches!(
+            Campagne::from_str("[Premier]\n[a = b]"),
+            Err(CampagneError::Syntax { line: 2 })
+        ));
+        assert!(matches!(
+            Campagne::from_str("[Premier]\n\ncarte premier.txt"),
+            Err(CampagneError::Syntax { line: 3 })
+        ));
+        assert!(matches!(
+            Campagne::from_str("[Premier]\nchefs = 2"),
+            Err(CampagneError::UnknownKey { line: 2, key }) if key == "chefs"
+        ));
+        assert!(matches!(
+            Campagne::from_str("[Premier]\netoiles_requises = 4"),
+            Err(CampagneError::InvalidValue { line: 2, key }) if key == "etoiles_requises"
+        ));
+        assert!(matches!(
+            Campagne::from_str("# rien\n"),
+            Err(CampagneError::Vide)
+        ));
+    }
+
+    #[test]
+    fn les_niveaux_se_debloquent_avec_les_etoiles() {
+        let campagne = Campagne::from_str(CAMPAGNE).unwrap();
+        let mut progression = Progression::default();
+        assert!(campagne.is_debloque(&progression, 0));
+        assert!(!campagne.is_debloque(&progression, 1));
+        assert_eq!(campagne.reprise(&progression), 0);
+
+        progression.enregistrer("Premier", 1);
+        progression.enregistrer("Deuxième", 1);
+        assert!(campagne.is_debloque(&progression, 1));
+        assert!(!campagne.is_debloque(&progression, 2));
+        assert_eq!(campagne.reprise(&progression), 1);
+
+        progression.enregistrer("Deuxième", 3);
+        assert!(campagne.is_debloque(&progression, 2));
+        assert!(!campagne.is_debloque(&progression, 3));
+        assert_eq!(campagne.reprise(&progression), 2);
+    }
+
+    #[test]
+    fn la_progression_garde_le_meilleur_resultat() {
+        let mut progression = Progression::default();
+        assert!(progression.enregistrer("Premier", 2));
+        assert!(!progression.enregistrer("Premier", 1));
+        assert!(!progression.enregistrer("Premier", 2));
+        assert_eq!(progression.get_etoiles("Premier"), 2);
+        assert_eq!(progression.get_etoiles("Inconnu"), 0);
+    }
+
+    #[test]
+    fn la_progression_se_sauvegarde_et_se_recharge() {
+        let chemin = temporaire("progression");
+        let mut progression = Progression::default();
+        progression.enregistrer("Premier", 3);
+        progression.enregistrer("Deuxième niveau", 1);
+        progression.sauvegarder(&chemin).unwrap();
+        let relue = Progression::charger(&chemin).unwrap();
+        fs::remove_file(&chemin).unwrap();
+        assert_eq!(relue, progression);
+
+        // sans fichier, la campagne commence
+        assert_eq!(
+            Progression::charger(&chemin).unwrap(),
+            Progression::default()
+        );
+    }
+
+    #[test]
+    fn une_progression_illisible_donne_sa_ligne() {
+        let chemin = temporaire("progression-illisible");
+        fs::write(&chemin, "Premier = 2\nDeuxième = beaucoup").unwrap();
+        let erreur = Progression::charger(&chemin);
+        fs::remove_file(&chemin).unwrap();
+        assert!(matches!(
+            erreur,
+            Err(CampagneError::InvalidValue { line: 2, key }) if key == "Deuxième"
+        ));
+    }
+}
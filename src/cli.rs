@@ -0,0 +1,216 @@
+use std::{ops::RangeInclusive, path::PathBuf, time::Duration};
+
+use color_eyre::Result;
+
+use crate::{
+    app::ControlMode,
+    config::{GameConfig, parse_secs},
+    game::Game,
+    livre::LivreRecettes,
+};
+
+pub const USAGE: &str = "Usage : hai716i_poasma [OPTIONS]
+  --mode human|robot|coop|headless
+                                qui contrôle les chefs (défaut : robot),
+                                coop : le chef 1 au clavier, les autres en robot
+  --map FICHIER                 carte au format texte
+  --config FICHIER              réglages de la partie (cle = valeur)
+  --recipes FICHIER             livre des recettes que les clients commandent
+  --campaign FICHIER            joue la campagne, niveau après niveau
+  --progress FICHIER            sauvegarde de la campagne (défaut : progression.txt)
+  --seed N                      graine de la partie
+  --chefs N                     nombre de chefs en cuisine
+  --duration SECONDES           durée de la partie
+  --robot-tick MS               temps entre deux actions du robot
+  --recipe-cooldown MIN..MAX    délai entre deux recettes, en secondes";
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Mode {
+    Tui(ControlMode),
+    Headless,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum CliError {
+    Help,
+    Unknown(String),
+    MissingValue(String),
+    InvalidValue { flag: String, value: String },
+}
+
+impl std::fmt::Display for CliError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CliError::Help => write!(f, "{USAGE}"),
+            CliError::Unknown(arg) => write!(f, "argument inconnu : {arg}\n{USAGE}"),
+            CliError::MissingValue(flag) => write!(f, "{flag} attend une valeur\n{USAGE}"),
+            CliError::InvalidValue { flag, value } => {
+                write!(f, "valeur invalide pour {flag} : {value}\n{USAGE}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for CliError {}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct Cli {
+    pub mode: Mode,
+    pub map: Option<PathBuf>,
+    pub config: Option<PathBuf>,
+    pub recettes: Option<PathBuf>,
+    pub campagne: Option<PathBuf>,
+    pub progression: Option<PathBuf>,
+    pub seed: Option<u64>,
+    pub chefs: Option<usize>,
+    pub duration: Option<Duration>,
+    pub robot_tick: Option<Duration>,
+    pub recette_cooldown: Option<RangeInclusive<Duration>>,
+}
+
+impl Default for Cli {
+    fn default() -> Self {
+        Self {
+            mode: Mode::Tui(ControlMode::default()),
+            map: None,
+            config: None,
+            recettes: None,
+            campagne: None,
+            progression: None,
+            seed: None,
+            chefs: None,
+            duration: None,
+            robot_tick: None,
+            recette_cooldown: None,
+        }
+    }
+}
+
+// `MIN..MAX` ou `MIN..=MAX` en secondes
+fn parse_intervalle_cli(text: &str) -> Option<RangeInclusive<Duration>> {
+    let (min, max) = text.split_once("..")?;
+    let (min, max) = (parse_secs(min)?, parse_secs(max.trim_start_matches('='))?);
+    (min <= max).then_some(min..=max)
+}
+
+impl Cli {
+    pub fn parse(args: impl IntoIterator<Item = String>) -> Result<Self, CliError> {
+        let mut cli = Self::default();
+        let mut args = args.into_iter();
+
+        while let Some(flag) = args.next() {
+            if flag == "--help" || flag == "-h" {
+                return Err(CliError::Help);
+            }
+
+            let value = args
+                .next()
+                .ok_or_else(|| CliError::MissingValue(flag.clone()))?;
+            let invalid = || CliError::InvalidValue {
+                flag: flag.clone(),
+                value: value.clone(),
+            };
+
+            match flag.as_str() {
+                "--mode" => {
+                    cli.mode = match value.as_str() {
+                        "headless" => Mode::Headless,
+                        _ => Mode::Tui(ControlMode::parse(&value).ok_or_else(invalid)?),
+                    }
+                }
+                "--map" => cli.map = Some(PathBuf::from(&value)),
+                "--config" => cli.config = Some(PathBuf::from(&value)),
+                "--recipes" => cli.recettes = Some(PathBuf::from(&value)),
+                "--campaign" => cli.campagne = Some(PathBuf::from(&value)),
+                "--progress" => cli.progression = Some(PathBuf::from(&value)),
+                "--seed" => cli.seed = Some(value.parse().map_err(|_| invalid())?),
+                "--chefs" => {
+                    let chefs = value.parse().ok().filter(|&chefs| chefs > 0);
+                    cli.chefs = Some(chefs.ok_or_else(invalid)?)
+                }
+                // une partie sans durée serait finie avant d'avoir commencé
+                "--duration" => {
+                    let duration = parse_secs(&value).filter(|duration| !duration.is_zero());
+                    cli.duration = Some(duration.ok_or_else(invalid)?)
+                }
+                "--robot-tick" => {
+                    cli.robot_tick =
+                        Some(Duration::from_millis(value.parse().map_err(|_| invalid())?))
+                }
+                "--recipe-cooldown" => {
+                    cli.recette_cooldown = Some(parse_intervalle_cli(&value).ok_or_else(invalid)?)
+                }
+                _ => return Err(CliError::Unknown(flag)),
+            }
+        }
+
+        Ok(cli)
+    }
+
+    // le fichier de configuration, puis les options qui le surchargent
+    pub fn build_config(&self) -> Result<GameConfig> {
+        let mut config = match &self.config {
+            Some(path) => GameConfig::from_file(path)?,
+            None => GameConfig::default(),
+        };
+        if let Some(path) = &self.recettes {
+            config.livre = LivreRecettes::from_file(path)?;
+        }
+        if let Some(chefs) = self.chefs {
+            config.chefs = chefs;
+        } else if self.mode == Mode::Tui(ControlMode::Coop) {
+            // au moins un robot pour accompagner le joueur
+            config.chefs = config.chefs.max(2);
+        }
+        if let Some(duration) = self.duration {
+            config.duree_partie = duration;
+        }
+        if let Some(robot_tick) = self.robot_tick {
+            config.robot_cooldown = robot_tick;
+        }
+        if let Some(recette_cooldown) = &self.recette_cooldown {
+            config.recette_cooldown = recette_cooldown.clone();
+        }
+        Ok(config)
+    }
+
+    // la graine n'est pas appliquée ici : sans --seed chaque partie en tire une nouvelle
+    pub fn build_game(&self) -> Result<Game> {
+        let config = self.build_config()?;
+        let game = match &self.map {
+            Some(path) => Game::from_map_file(path, config)?,
+            None => Game::new(config)?,
+        };
+        Ok(game)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args: &[&str]) -> Result<Cli, CliError> {
+        Cli::parse(args.iter().map(|arg| arg.to_string()))
+    }
+
+    #[test]
+    fn une_duree_nulle_est_refusee() {
+        assert!(matches!(
+            parse(&["--duration", "0"]),
+            Err(CliError::InvalidValue { flag, .. }) if flag == "--duration"
+        ));
+        assert_eq!(
+            parse(&["--duration", "1.5"]).unwrap().duration,
+            Some(Duration::from_millis(1500))
+        );
+    }
+
+    #[test]
+    fn l_intervalle_s_ecrit_avec_deux_points() {
+        let attendu = Some(Duration::from_secs(2)..=Duration::from_secs(5));
+        assert_eq!(parse_intervalle_cli("2..5"), attendu);
+        assert_eq!(parse_intervalle_cli("2..=5"), attendu);
+        assert_eq!(parse_intervalle_cli("5..2"), None);
+        assert_eq!(parse_intervalle_cli("[2, 5]"), None);
+    }
+}
@@ -0,0 +1,28 @@
+use std::time::{Duration, Instant};
+
+// Source du temps de la partie : l'horloge système ou un temps simulé qu'on avance à la main
+#[derive(Debug, PartialEq, Clone, Copy, Default)]
+pub enum Clock {
+    #[default]
+    Real,
+    Manual(Instant),
+}
+
+impl Clock {
+    pub fn manual() -> Self {
+        Clock::Manual(Instant::now())
+    }
+
+    pub fn now(&self) -> Instant {
+        match self {
+            Clock::Real => Instant::now(),
+            Clock::Manual(now) => *now,
+        }
+    }
+
+    pub fn advance(&mut self, duration: Duration) {
+        if let Clock::Manual(now) = self {
+            *now += duration;
+        }
+    }
+}
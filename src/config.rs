@@ -0,0 +1,412 @@
+use std::{fs, io, ops::RangeInclusive, path::Path, str::FromStr, time::Duration};
+
+use crate::livre::LivreRecettes;
+
+#[derive(Debug)]
+pub enum ConfigError {
+    Io(io::Error),
+    Syntax { line: usize },
+    UnknownKey { line: usize, key: String },
+    InvalidValue { line: usize, key: String },
+}
+
+impl From<io::Error> for ConfigError {
+    fn from(error: io::Error) -> Self {
+        ConfigError::Io(error)
+    }
+}
+
+impl std::fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ConfigError::Io(error) => write!(f, "lecture de la configuration impossible : {error}"),
+            ConfigError::Syntax { line } => write!(f, "ligne {line} : `cle = valeur` attendu"),
+            ConfigError::UnknownKey { line, key } => write!(f, "ligne {line} : clé inconnue {key}"),
+            ConfigError::InvalidValue { line, key } => {
+                write!(f, "ligne {line} : valeur invalide pour {key}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for ConfigError {}
+
+// Réglages d'une partie, chargeables depuis un fichier `cle = valeur` (sous-ensemble de TOML)
+#[derive(Debug, PartialEq, Clone)]
+pub struct GameConfig {
+    pub duree_partie: Duration,
+    pub recette_cooldown: RangeInclusive<Duration>,
+    // délai d'une recette : base + par ingrédient - malus par paire d'ingrédients
+    pub delai_recette_base: RangeInclusive<Duration>,
+    pub delai_par_ingredient: Duration,
+    pub delai_malus_par_paire: Duration,
+    // points gagnés par ingrédient livré
+    pub points_par_ingredient: i32,
+    // pourboire d'une commande servie aussitôt, proportionnel au temps qu'il lui restait
+    pub pourboire_max: i32,
+    // chaque livraison d'affilée augmente le multiplicateur des suivantes, jusqu'au maximum ;
+    // une commande expirée remet la série à zéro
+    pub bonus_serie: f32,
+    pub multiplicateur_max: f32,
+    // bonus d'une recette ordonnée empilée exactement dans l'ordre du livre
+    pub bonus_ordre: i32,
+    // une recette expirée coûte cette pénalité moins son nombre d'ingrédients, jamais moins de zéro
+    pub penalite_expiration: i32,
+    // points perdus par ingrédient jeté à la poubelle
+    pub penalite_poubelle: i32,
+    pub recettes_min: usize,
+    pub recettes_max: Option<usize>,
+    // scores à atteindre pour une, deux puis trois étoiles
+    pub etoiles: [i32; 3],
+    // la partie est perdue dès que ce nombre de commandes a expiré
+    pub expirations_max: Option<usize>,
+    pub robot_cooldown: Duration,
+    // nombre de chefs en cuisine, il faut autant de points d'apparition sur la carte
+    pub chefs: usize,
+    // coups de couteau pour couper un ingrédient sur la planche
+    pub coups_decoupe: u32,
+    // temps avant qu'une assiette servie revienne sale, puis coups d'éponge pour la laver
+    pub delai_retour_assiette: Duration,
+    pub coups_lavage: u32,
+    // temps pour cuire la viande, puis avant qu'elle ne brûle
+    pub duree_cuisson: Duration,
+    pub delai_brulure: Duration,
+    // recettes que les clients peuvent commander
+    pub livre: LivreRecettes,
+}
+
+impl Default for GameConfig {
+    fn default() -> Self {
+        Self {
+            duree_partie: Duration::from_secs(120),
+            recette_cooldown: Duration::from_secs(10)..=Duration::from_secs(25),
+            delai_recette_base: Duration::from_secs(7)..=Duration::from_secs(20),
+            delai_par_ingredient: Duration::from_secs(5),
+            delai_malus_par_paire: Duration::from_secs(1),
+            points_par_ingredient: 2,
+            pourboire_max: 4,
+            bonus_serie: 0.1,
+            multiplicateur_max: 1.5,
+            bonus_ordre: 3,
+            penalite_expiration: 4,
+            penalite_poubelle: 0,
+            recettes_min: 2,
+            recettes_max: None,
+            etoiles: [20, 35, 50],
+            expirations_max: None,
+            robot_cooldown: Duration::from_millis(100),
+            chefs: 1,
+            coups_decoupe: 6,
+            delai_retour_assiette: Duration::from_secs(5),
+            coups_lavage: 4,
+            duree_cuisson: Duration::from_secs(5),
+            delai_brulure: Duration::from_secs(6),
+            livre: LivreRecettes::default(),
+        }
+    }
+}
+
+pub(crate) fn parse_secs(text: &str) -> Option<Duration> {
+    text.parse::<f32>()
+        .ok()
+        .and_then(|secs| Duration::try_from_secs_f32(secs).ok())
+}
+
+// `[une, deux, trois]` étoiles, dans l'ordre croissant
+fn parse_etoiles(text: &str) -> Option<[i32; 3]> {
+    let seuils = text
+        .strip_prefix('[')?
+        .strip_suffix(']')?
+        .split(',')
+        .map(|seuil| seuil.trim().parse().ok())
+        .collect::<Option<Vec<i32>>>()?;
+    let seuils: [i32; 3] = seuils.try_into().ok()?;
+    seuils.is_sorted().then_some(seuils)
+}
+
+// `[min, max]` en secondes
+pub(crate) fn parse_range(text: &str) -> Option<RangeInclusive<Duration>> {
+    let (min, max) = text.strip_prefix('[')?.strip_suffix(']')?.split_once(',')?;
+    let (min, max) = (parse_secs(min.trim())?, parse_secs(max.trim())?);
+    (min <= max).then_some(min..=max)
+}
+
+impl FromStr for GameConfig {
+    type Err = ConfigError;
+
+    // les clés absentes gardent leur valeur par défaut
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        let mut config = Self::default();
+        // dernière ligne donnant recettes_min ou recettes_max, pour signaler un écart
+        let mut ligne_recettes = None;
+
+        for (i, line) in text.lines().enumerate() {
+            let line_number = i + 1;
+            let line = line.split('#').next().unwrap_or_default().trim();
+            if line.is_empty() {
+                continue;
+            }
+
+            let (key, value) = line
+                .split_once('=')
+                .ok_or(ConfigError::Syntax { line: line_number })?;
+            let (key, value) = (key.trim(), value.trim());
+            let invalid = || ConfigError::InvalidValue {
+                line: line_number,
+                key: key.to_string(),
+            };
+
+            match key {
+                "duree_partie" => config.duree_partie = parse_secs(value).ok_or_else(invalid)?,
+                "recette_cooldown" => {
+                    config.recette_cooldown = parse_range(value).ok_or_else(invalid)?
+                }
+                "delai_recette_base" => {
+                    config.delai_recette_base = parse_range(value).ok_or_else(invalid)?
+                }
+                "delai_par_ingredient" => {
+                    config.delai_par_ingredient = parse_secs(value).ok_or_else(invalid)?
+                }
+                "delai_malus_par_paire" => {
+                    config.delai_malus_par_paire = parse_secs(value).ok_or_else(invalid)?
+                }
+                "points_par_ingredient" => {
+                    config.points_par_ingredient = value.parse().map_err(|_| invalid())?
+                }
+                "pourboire_max" => config.pourboire_max = value.parse().map_err(|_| invalid())?,
+                "bonus_serie" => {
+                    config.bonus_serie = value
+                        .parse()
+                        .ok()
+                        .filter(|&bonus: &f32| bonus >= 0.)
+                        .ok_or_else(invalid)?
+                }
+                "multiplicateur_max" => {
+                    config.multiplicateur_max = value
+                        .parse()
+                        .ok()
+                        .filter(|&max: &f32| max >= 1.)
+                        .ok_or_else(invalid)?
+                }
+                "bonus_ordre" => config.bonus_ordre = value.parse().map_err(|_| invalid())?,
+                "penalite_expiration" => {
+                    config.penalite_expiration = value
+                        .parse()
+                        .ok()
+                        .filter(|&penalite| penalite >= 0)
+                        .ok_or_else(invalid)?
+                }
+                "penalite_poubelle" => {
+                    config.penalite_poubelle = value.parse().map_err(|_| invalid())?
+                }
+                "recettes_min" => {
+                    config.recettes_min = value.parse().map_err(|_| invalid())?;
+                    ligne_recettes = Some((line_number, key.to_string()));
+                }
+                "recettes_max" => {
+                    config.recettes_max = Some(value.parse().map_err(|_| invalid())?);
+                    ligne_recettes = Some((line_number, key.to_string()));
+                }
+                "etoiles" => config.etoiles = parse_etoiles(value).ok_or_else(invalid)?,
+                "expirations_max" => {
+                    config.expirations_max = Some(
+                        value
+                            .parse()
+                            .ok()
+                            .filter(|&max| max > 0)
+                            .ok_or_else(invalid)?,
+                    )
+                }
+                "robot_cooldown" => {
+                    config.robot_cooldown = parse_secs(value).ok_or_else(invalid)?
+                }
+                "chefs" => {
+                    config.chefs = value
+                        .parse()
+                        .ok()
+                        .filter(|&chefs| chefs > 0)
+                        .ok_or_else(invalid)?
+                }
+                "coups_decoupe" => {
+                    config.coups_decoupe = value
+                        .parse()
+                        .ok()
+                        .filter(|&coups| coups > 0)
+                        .ok_or_else(invalid)?
+                }
+                "delai_retour_assiette" => {
+                    config.delai_retour_assiette = parse_secs(value).ok_or_else(invalid)?
+                }
+                "coups_lavage" => {
+                    config.coups_lavage = value
+                        .parse()
+                        .ok()
+                        .filter(|&coups| coups > 0)
+                        .ok_or_else(invalid)?
+                }
+                "duree_cuisson" => {
+                    config.duree_cuisson = parse_secs(value)
+                        .filter(|duree| !duree.is_zero())
+                        .ok_or_else(invalid)?
+                }
+                "delai_brulure" => config.delai_brulure = parse_secs(value).ok_or_else(invalid)?,
+                _ => {
+                    return Err(ConfigError::UnknownKey {
+                        line: line_number,
+                        key: key.to_string(),
+                    });
+                }
+            }
+        }
+
+        if let Some((line, key)) = ligne_recettes
+            && config
+                .recettes_max
+                .is_some_and(|max| config.recettes_min > max)
+        {
+            return Err(ConfigError::InvalidValue { line, key });
+        }
+
+        Ok(config)
+    }
+}
+
+impl GameConfig {
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self, ConfigError> {
+        let text = fs::read_to_string(path)?;
+        Self::from_str(&text)
+    }
+
+    pub fn delai_recette(&self, ingredient_count: usize) -> RangeInclusive<Duration> {
+        let bonus = self.delai_par_ingredient * ingredient_count as u32;
+        let malus = self.delai_malus_par_paire * (ingredient_count / 2) as u32;
+        let min = (*self.delai_recette_base.start() + bonus).saturating_sub(malus);
+        let max = (*self.delai_recette_base.end() + bonus).saturating_sub(malus);
+        min..=max
+    }
+
+    pub fn penalite(&self, ingredient_count: usize) -> i32 {
+        (self.penalite_expiration - ingredient_count as i32).max(0)
+    }
+
+    pub fn etoiles(&self, score: i32) -> usize {
+        self.etoiles.iter().filter(|&&seuil| score >= seuil).count()
+    }
+
+    pub fn peut_ajouter_recette(&self, recettes: usize) -> bool {
+        self.recettes_max.is_none_or(|max| recettes < max)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn les_configurations_fournies_se_lisent() {
+        for text in [
+            include_str!("../configs/facile.toml"),
+            include_str!("../configs/normal.toml"),
+            include_str!("../configs/difficile.toml"),
+        ] {
+            GameConfig::from_str(text).unwrap();
+        }
+    }
+
+    #[test]
+    fn les_cles_absentes_gardent_leur_valeur_par_defaut() {
+        let config = GameConfig::from_str(
+            "# commentaire\n\npoints_par_ingredient = 3 # trois\nrecette_cooldown = [1, 2.5]",
+        )
+        .unwrap();
+        assert_eq!(config.points_par_ingredient, 3);
+        assert_eq!(
+            config.recette_cooldown,
+            Duration::from_secs(1)..=Duration::from_millis(2500)
+        );
+        assert_eq!(config.duree_partie, GameConfig::default().duree_partie);
+    }
+
+    #[test]
+    fn les_erreurs_donnent_leur_ligne() {
+        assert!(matches!(
+            GameConfig::from_str("recettes_min = 1\nrecettes_min 2"),
+            Err(ConfigError::Syntax { line: 2 })
+        ));
+        assert!(matches!(
+            GameConfig::from_str("\nvitesse = 2"),
+            Err(ConfigError::UnknownKey { line: 2, key }) if key == "vitesse"
+        ));
+        assert!(matches!(
+            GameConfig::from_str("recettes_min = deux"),
+            Err(ConfigError::InvalidValue { line: 1, key }) if key == "recettes_min"
+        ));
+        assert!(matches!(
+            GameConfig::from_str("recette_cooldown = [5, 1]"),
+            Err(ConfigError::InvalidValue { line: 1, .. })
+        ));
+    }
+
+    #[test]
+    fn le_delai_grandit_avec_les_ingredients() {
+        let config = GameConfig::default();
+        assert_eq!(config.delai_recette(0), config.delai_recette_base);
+        assert_eq!(
+            *config.delai_recette(4).start(),
+            *config.delai_recette_base.start() + config.delai_par_ingredient * 4
+                - config.delai_malus_par_paire * 2
+        );
+    }
+
+    #[test]
+    fn recettes_max_limite_les_commandes() {
+        let config = GameConfig::from_str("recettes_max = 2").unwrap();
+        assert!(config.peut_ajouter_recette(1));
+        assert!(!config.peut_ajouter_recette(2));
+        assert!(GameConfig::default().peut_ajouter_recette(100));
+    }
+
+    #[test]
+    fn une_grosse_recette_coute_moins_cher_a_rater() {
+        let config = GameConfig {
+            penalite_expiration: 4,
+            ..GameConfig::default()
+        };
+        assert_eq!(config.penalite(1), 3);
+        assert_eq!(config.penalite(3), 1);
+    }
+
+    #[test]
+    fn la_penalite_d_expiration_n_est_jamais_un_gain() {
+        let config = GameConfig {
+            penalite_expiration: 4,
+            ..GameConfig::default()
+        };
+        assert_eq!(config.penalite(6), 0);
+        assert!(matches!(
+            GameConfig::from_str("penalite_expiration = -1"),
+            Err(ConfigError::InvalidValue { line: 1, .. })
+        ));
+    }
+
+    #[test]
+    fn recettes_min_ne_depasse_pas_recettes_max() {
+        assert!(matches!(
+            GameConfig::from_str("recettes_max = 2\n\nrecettes_min = 3"),
+            Err(ConfigError::InvalidValue { line: 3, key }) if key == "recettes_min"
+        ));
+        assert!(GameConfig::from_str("recettes_min = 2\nrecettes_max = 2").is_ok());
+    }
+
+    #[test]
+    fn les_etoiles_suivent_les_seuils() {
+        let config = GameConfig::default();
+        assert_eq!(config.etoiles(config.etoiles[0] - 1), 0);
+        assert_eq!(config.etoiles(config.etoiles[0]), 1);
+        assert_eq!(config.etoiles(config.etoiles[2] + 100), 3);
+        assert!(matches!(
+            GameConfig::from_str("etoiles = [30, 20, 50]"),
+            Err(ConfigError::InvalidValue { line: 1, key }) if key == "etoiles"
+        ));
+    }
+}
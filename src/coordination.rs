@@ -0,0 +1,655 @@
+use std::{
+    collections::{BTreeSet, HashMap, HashSet, VecDeque},
+    time::Duration,
+};
+
+use crate::{
+    game::{Game, RobotAction},
+    objets::{Case, Contenu, Direction, Ingredient, IngredientEtat, Objet, Recette},
+    pathfinding::{DistanceMap, Pathfinder, is_walkable, neighbours},
+    strategy::{
+        Strategy, assiette_de_travail, contourner, debarras, destination_assiette,
+        ingredient_en_trop, recette_proche,
+    },
+};
+
+// détour accepté pour un ingrédient déjà prêt plutôt qu'un ingrédient à couper
+const COUT_PREPARATION: usize = 4;
+// actions passées bloqué avant de céder le passage, puis à laisser passer les autres
+const PATIENCE: usize = 10;
+
+// Sous-tâche confiée à un chef, toujours liée à une case précise de la carte
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Tache {
+    // vider une poêle brûlée ou reprendre une assiette sale
+    Debarrasser,
+    // retirer de l'assiette en cours un ingrédient que la recette visée ne demande pas
+    Retirer(Ingredient),
+    Chercher(Ingredient),
+    Couper(Ingredient),
+    Cuire(Ingredient),
+    Assembler(Ingredient),
+    // poser sur une table libre ce qui ne sert pas tout de suite, une assiette sur un emplacement
+    Ranger,
+    // prendre l'assiette terminée, puis la poser sur la passe
+    Emporter,
+    Servir,
+    // mettre une assiette dans l'évier, puis la laver
+    Plonger,
+    Laver,
+    // jeter un ingrédient inutile ou vider une assiette ratée
+    Jeter,
+}
+
+impl Tache {
+    fn action(&self) -> RobotAction {
+        match self {
+            Tache::Debarrasser | Tache::Chercher(_) | Tache::Emporter | Tache::Laver => {
+                RobotAction::Pickup
+            }
+            Tache::Retirer(ingredient) => RobotAction::Retirer(*ingredient),
+            Tache::Couper(_)
+            | Tache::Cuire(_)
+            | Tache::Assembler(_)
+            | Tache::Ranger
+            | Tache::Servir
+            | Tache::Plonger
+            | Tache::Jeter => RobotAction::Deposit,
+        }
+    }
+
+    // ce qu'il reste à faire d'un ingrédient en main pour obtenir `voulu`
+    fn preparer(en_main: Ingredient, voulu: Ingredient) -> Self {
+        if en_main == voulu {
+            Tache::Assembler(voulu)
+        } else if en_main.se_cuit() {
+            Tache::Cuire(voulu)
+        } else {
+            Tache::Couper(voulu)
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct Affectation {
+    pub tache: Tache,
+    pub cible: (usize, usize),
+}
+
+// Répartit le travail entre plusieurs chefs robots : une sous-tâche chacun,
+// une table réservée par chef, et priorité au plus petit numéro dans les couloirs
+#[derive(Debug, Default, Clone)]
+pub struct Coordinateur {
+    pathfinder: Pathfinder,
+    // chefs pilotés par le coordinateur, les autres (humains) sont seulement pris en compte
+    agents: BTreeSet<usize>,
+    affectations: HashMap<usize, Affectation>,
+    // distances depuis chaque chef, le temps d'une planification
+    distances: HashMap<usize, DistanceMap>,
+    // chemins prévus par chaque chef, que les autres doivent laisser libres
+    chemins: HashMap<usize, Vec<(usize, usize)>>,
+    // position de chaque chef en route et nombre d'actions passées sans en bouger
+    immobiles: HashMap<usize, ((usize, usize), usize)>,
+    // actions restantes pendant lesquelles le chef laisse passer les autres
+    attentes: HashMap<usize, usize>,
+    // chefs déjà servis par la planification en cours et cuisine sur laquelle elle a été faite :
+    // un tour de robots n'en fait qu'une, sauf si un chef a pris ou posé quelque chose entre-temps
+    servis: BTreeSet<usize>,
+    cuisine: (Vec<Vec<Case>>, Vec<Option<Objet>>),
+}
+
+impl Coordinateur {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn get_affectations(&self) -> &HashMap<usize, Affectation> {
+        &self.affectations
+    }
+}
+
+impl Strategy for Coordinateur {
+    fn next_action(&mut self, game: &Game, id: usize) -> RobotAction {
+        // un chef qui redemande, ou un nouveau venu, ouvre un nouveau tour
+        let cuisine = (
+            game.get_map().clone(),
+            game.get_players()
+                .iter()
+                .map(|player| player.get_object_held())
+                .collect::<Vec<_>>(),
+        );
+        if self.agents.insert(id) || self.servis.contains(&id) || self.cuisine != cuisine {
+            self.servis.clear();
+            self.cuisine = cuisine;
+            self.planifier(game);
+        }
+        self.servis.insert(id);
+        if let Some(attente) = self.attentes.get_mut(&id)
+            && *attente > 0
+        {
+            *attente -= 1;
+            return self.degager(game, id);
+        }
+
+        match self.affectations.get(&id).copied() {
+            Some(affectation) => self.agir(game, id, affectation),
+            None => self.degager(game, id),
+        }
+    }
+}
+
+fn cases(map: &[Vec<Case>]) -> impl Iterator<Item = ((usize, usize), Case)> + '_ {
+    map.iter()
+        .enumerate()
+        .flat_map(|(y, row)| row.iter().enumerate().map(move |(x, case)| ((x, y), *case)))
+}
+
+// la recette la plus proche de l'assiette en cours
+fn recette_visee(game: &Game) -> Option<&Recette> {
+    let (_, assiette) = assiette_de_travail(game)?;
+    recette_proche(game, &assiette.contenu())
+}
+
+impl Coordinateur {
+    fn distance(&mut self, game: &Game, id: usize, cible: (usize, usize)) -> Option<usize> {
+        self.distances.get(&id)?.distance_to(game.get_map(), cible)
+    }
+
+    // confie la tâche au chef libre le plus proche de la cible
+    fn confier(
+        &mut self,
+        game: &Game,
+        libres: &mut Vec<usize>,
+        tache: Tache,
+        cible: (usize, usize),
+    ) -> bool {
+        let chef = libres
+            .iter()
+            .filter_map(|&id| Some((self.distance(game, id, cible)?, id)))
+            .min();
+        let Some((_, id)) = chef else {
+            return false;
+        };
+        libres.retain(|&libre| libre != id);
+        self.affectations.insert(id, Affectation { tache, cible });
+        true
+    }
+
+    fn plus_proche(
+        &mut self,
+        game: &Game,
+        id: usize,
+        reservees: &HashSet<(usize, usize)>,
+        predicate: impl Fn(&Case) -> bool,
+    ) -> Option<(usize, usize)> {
+        cases(game.get_map())
+            .filter(|(pos, case)| predicate(case) && !reservees.contains(pos))
+            .filter_map(|(pos, _)| Some((self.distance(game, id, pos)?, pos)))
+            .min()
+            .map(|(_, pos)| pos)
+    }
+
+    fn planifier(&mut self, game: &Game) {
+        self.affectations.clear();
+        self.distances = game
+            .get_players()
+            .iter()
+            .filter(|player| self.agents.contains(&player.get_id()))
+            .map(|player| {
+                let distances = self
+                    .pathfinder
+                    .distances_from(game.get_map(), player.get_pos());
+                (player.get_id(), distances.clone())
+            })
+            .collect();
+        let mut reservees = HashSet::new();
+
+        let travail = assiette_de_travail(game);
+        let contenu = travail.map_or_else(Contenu::default, |(_, assiette)| assiette.contenu());
+        let recette = recette_visee(game);
+        // une recette ordonnée n'attend que la couche en cours, les suivantes se préparent à côté
+        let (mut manquants, plus_tard) = match (recette, travail) {
+            (Some(recette), Some((_, travail))) => {
+                let prochains = recette.prochains(&travail);
+                let plus_tard = recette
+                    .get_ingredients()
+                    .difference(&contenu)
+                    .difference(&prochains);
+                (prochains.iter().collect::<Vec<_>>(), plus_tard)
+            }
+            _ => (vec![], Contenu::default()),
+        };
+        let en_trop = recette.is_some_and(|recette| {
+            travail.is_some_and(|(_, travail)| !recette.peut_completer(&travail))
+        });
+        let complete = recette
+            .is_some_and(|recette| travail.is_some_and(|(_, travail)| recette.accepte(&travail)));
+
+        // ce que demandent les autres recettes, moins ce qui attend déjà sur les tables
+        let mut a_preparer = game
+            .get_recettes()
+            .iter()
+            .filter(|autre| recette.is_none_or(|recette| !recette.is_same(autre)))
+            .flat_map(|autre| autre.get_ingredients().iter())
+            .chain(plus_tard.iter())
+            .collect::<Vec<_>>();
+        a_preparer.sort();
+        // ces tables ne sont plus des sources pour les recettes suivantes, qui les comptent déjà
+        let mut en_attente = Vec::new();
+        for (pos, case) in cases(game.get_map()) {
+            if let Case::Table(Some(ingredient)) = case
+                && let Some(i) = a_preparer
+                    .iter()
+                    .position(|voulu| ingredient.peut_devenir(voulu))
+            {
+                a_preparer.remove(i);
+                en_attente.push(pos);
+            }
+        }
+
+        // 1. chaque chef qui a quelque chose en main le fait avancer, humains compris
+        let mut libres = Vec::new();
+        for player in game.get_players() {
+            let id = player.get_id();
+            let Some(en_main) = player.get_object_held() else {
+                if self.agents.contains(&id) {
+                    libres.push(id);
+                }
+                continue;
+            };
+
+            let en_main = match en_main {
+                Objet::Ingredient(ingredient) => ingredient,
+                Objet::Assiette(assiette) => {
+                    let destination = destination_assiette(game, &assiette);
+                    let tache = match destination {
+                        Case::PASSE => Tache::Servir,
+                        Case::EVIER(_) => Tache::Plonger,
+                        Case::POUBELLE => Tache::Jeter,
+                        _ => Tache::Ranger,
+                    };
+                    if self.agents.contains(&id)
+                        && let Some(cible) =
+                            self.plus_proche(game, id, &reservees, |case| *case == destination)
+                    {
+                        if tache != Tache::Jeter {
+                            reservees.insert(cible);
+                        }
+                        self.affectations.insert(id, Affectation { tache, cible });
+                    }
+                    continue;
+                }
+            };
+            let tache = if let Some(i) = manquants
+                .iter()
+                .position(|voulu| en_main.peut_devenir(voulu))
+            {
+                let voulu = manquants.remove(i);
+                if en_trop {
+                    Tache::Ranger
+                } else {
+                    Tache::preparer(en_main, voulu)
+                }
+            } else if let Some(i) = a_preparer
+                .iter()
+                .position(|voulu| en_main.peut_devenir(voulu))
+            {
+                let voulu = a_preparer.remove(i);
+                match Tache::preparer(en_main, voulu) {
+                    Tache::Assembler(_) => Tache::Ranger,
+                    tache => tache,
+                }
+            } else {
+                Tache::Ranger
+            };
+            let tache = if tache == Tache::Ranger && debarras(game, &en_main) == Case::POUBELLE {
+                Tache::Jeter
+            } else {
+                tache
+            };
+
+            if !self.agents.contains(&id) {
+                continue;
+            }
+            let cible = match tache {
+                Tache::Assembler(_) => travail.map(|(pos, _)| pos),
+                Tache::Couper(_) => {
+                    self.plus_proche(game, id, &reservees, |case| *case == Case::COUPER(None))
+                }
+                Tache::Cuire(_) => {
+                    self.plus_proche(game, id, &reservees, |case| *case == Case::POELE(None))
+                }
+                Tache::Jeter => {
+                    self.plus_proche(game, id, &reservees, |case| *case == Case::POUBELLE)
+                }
+                _ => self.plus_proche(game, id, &reservees, |case| *case == Case::Table(None)),
+            };
+            if let Some(cible) = cible {
+                if matches!(tache, Tache::Ranger | Tache::Couper(_) | Tache::Cuire(_)) {
+                    reservees.insert(cible);
+                }
+                self.affectations.insert(id, Affectation { tache, cible });
+            }
+        }
+
+        // 2. un seul chef s'occupe de l'assiette en cours : la corriger ou l'emporter
+        if let Some((cible, assiette)) = travail
+            && (en_trop || complete)
+        {
+            let tache = match ingredient_en_trop(game, &assiette) {
+                Some(intrus) => Tache::Retirer(intrus),
+                None => Tache::Emporter,
+            };
+            if self.confier(game, &mut libres, tache, cible) {
+                reservees.insert(cible);
+            }
+        }
+        let brulees = cases(game.get_map())
+            .filter(|(_, case)| {
+                matches!(case, Case::POELE(Some(cuisson)) if cuisson.ingredient.etat == IngredientEtat::Brule)
+            })
+            .map(|(pos, _)| pos)
+            .collect::<Vec<_>>();
+        for cible in brulees {
+            if self.confier(game, &mut libres, Tache::Debarrasser, cible) {
+                reservees.insert(cible);
+            }
+        }
+
+        // 3. les ingrédients qui manquent à la recette visée
+        self.repartir(game, &mut libres, manquants, &mut reservees);
+
+        // 4. la vaisselle, avant de préparer les recettes suivantes
+        let a_laver = cases(game.get_map())
+            .filter(|(pos, case)| matches!(case, Case::EVIER(Some(_))) && !reservees.contains(pos))
+            .map(|(pos, _)| pos)
+            .collect::<Vec<_>>();
+        for cible in a_laver {
+            if self.confier(game, &mut libres, Tache::Laver, cible) {
+                reservees.insert(cible);
+            }
+        }
+        // une assiette sale à rapporter par évier libre
+        let eviers_libres = cases(game.get_map())
+            .filter(|(pos, case)| *case == Case::EVIER(None) && !reservees.contains(pos))
+            .count();
+        if let Some((cible, Case::RETOUR(sales))) =
+            cases(game.get_map()).find(|(_, case)| matches!(case, Case::RETOUR(_)))
+        {
+            for _ in 0..sales.min(eviers_libres) {
+                self.confier(game, &mut libres, Tache::Debarrasser, cible);
+            }
+        }
+
+        // 5. les ingrédients des recettes suivantes
+        reservees.extend(en_attente);
+        self.repartir(game, &mut libres, a_preparer, &mut reservees);
+
+        self.chemins = self
+            .affectations
+            .iter()
+            .filter_map(|(&id, affectation)| {
+                let route = self.distances[&id].route_to(game.get_map(), affectation.cible)?;
+                Some((id, route.path))
+            })
+            .collect();
+        // laisse de la place autour des chefs qu'on ne pilote pas (joueurs humains)
+        for player in game.get_players() {
+            if !self.agents.contains(&player.get_id()) {
+                let mut autour = vec![player.get_pos()];
+                autour.extend(neighbours(game.get_map(), player.get_pos()));
+                self.chemins.insert(player.get_id(), autour);
+            }
+        }
+        // les chefs inoccupés libèrent le passage, deux fois pour propager les replis en chaîne
+        for _ in 0..2 {
+            for &id in &self.agents {
+                if !self.affectations.contains_key(&id)
+                    && !self.chemins.contains_key(&id)
+                    && let Some(chemin) = self.repli(game, id)
+                {
+                    self.chemins.insert(id, chemin);
+                }
+            }
+        }
+    }
+
+    // à chaque tour, le couple (chef, ingrédient) le moins coûteux
+    fn repartir(
+        &mut self,
+        game: &Game,
+        libres: &mut Vec<usize>,
+        mut demandes: Vec<Ingredient>,
+        reservees: &mut HashSet<(usize, usize)>,
+    ) {
+        let map = game.get_map();
+        loop {
+            let mut meilleur: Option<(usize, usize, usize, (usize, usize))> = None;
+            let sources = demandes
+                .iter()
+                .map(|demande| {
+                    cases(map)
+                        .filter(|(pos, _)| !reservees.contains(pos))
+                        .filter_map(|(pos, case)| Some((pos, surcout(game, demande, &case)?)))
+                        .collect::<Vec<_>>()
+                })
+                .collect::<Vec<_>>();
+            for &id in libres.iter() {
+                for (i, sources) in sources.iter().enumerate() {
+                    for &(pos, surcout) in sources {
+                        let Some(distance) = self.distance(game, id, pos) else {
+                            continue;
+                        };
+                        let cout = distance + surcout;
+                        if meilleur.is_none_or(|(meilleur_cout, ..)| cout < meilleur_cout) {
+                            meilleur = Some((cout, id, i, pos));
+                        }
+                    }
+                }
+            }
+
+            let Some((_, id, i, cible)) = meilleur else {
+                return;
+            };
+            let demande = demandes.remove(i);
+            libres.retain(|&libre| libre != id);
+            if matches!(
+                map[cible.1][cible.0],
+                Case::Table(_) | Case::COUPER(_) | Case::POELE(_)
+            ) {
+                reservees.insert(cible);
+            }
+            let tache = Tache::Chercher(demande);
+            self.affectations.insert(id, Affectation { tache, cible });
+        }
+    }
+
+    fn agir(&mut self, game: &Game, id: usize, affectation: Affectation) -> RobotAction {
+        let player = game.get_player(id);
+        let Some(route) = self.distances[&id].route_to(game.get_map(), affectation.cible) else {
+            return self.degager(game, id);
+        };
+
+        if !route.is_arrived() {
+            let immobile = self.immobiles.entry(id).or_insert((player.get_pos(), 0));
+            if immobile.0 == player.get_pos() {
+                immobile.1 += 1;
+            } else {
+                *immobile = (player.get_pos(), 0);
+            }
+            // bloqué depuis trop longtemps : les priorités s'inversent
+            if immobile.1 >= PATIENCE {
+                self.immobiles.remove(&id);
+                self.attentes.insert(id, PATIENCE);
+                return self.degager(game, id);
+            }
+            return contourner(game, id, &route, || self.degager(game, id));
+        }
+        let direction = route.next_direction();
+        if player.get_facing() != direction {
+            return RobotAction::Deplacer(direction);
+        }
+        // attend la fin de la cuisson
+        if let (_, Case::POELE(Some(cuisson))) = game.get_facing(id)
+            && matches!(affectation.tache, Tache::Chercher(_))
+            && !cuisson.is_pret()
+        {
+            return RobotAction::None;
+        }
+        affectation.tache.action()
+    }
+}
+
+impl Coordinateur {
+    // quitte les chemins des autres chefs en suivant son chemin de repli
+    fn degager(&self, game: &Game, id: usize) -> RobotAction {
+        let repli = if self.affectations.contains_key(&id) {
+            self.repli(game, id)
+        } else {
+            self.chemins.get(&id).cloned()
+        };
+        match repli.as_deref() {
+            Some([depart, pas, ..]) if !game.is_occupied(*pas) => {
+                Direction::between(*depart, *pas).map_or(RobotAction::None, RobotAction::Deplacer)
+            }
+            _ => RobotAction::None,
+        }
+    }
+
+    // chemin vers la case libre la plus proche hors des chemins des autres chefs,
+    // en passant au besoin par les chefs inoccupés qui s'écarteront à leur tour
+    fn repli(&self, game: &Game, id: usize) -> Option<Vec<(usize, usize)>> {
+        let map = game.get_map();
+        let depart = game.get_player(id).get_pos();
+        let genantes = self
+            .chemins
+            .iter()
+            .filter(|(autre, _)| **autre != id)
+            .flat_map(|(_, chemin)| chemin.iter().copied())
+            .collect::<HashSet<_>>();
+        if !genantes.contains(&depart) {
+            return None;
+        }
+
+        let occupees = game
+            .get_players()
+            .iter()
+            .filter(|player| player.get_id() != id)
+            .filter(|player| self.affectations.contains_key(&player.get_id()))
+            .map(|player| player.get_pos())
+            .collect::<HashSet<_>>();
+        let mut precedents = HashMap::from([(depart, depart)]);
+        let mut a_visiter = VecDeque::from([depart]);
+        while let Some(pos) = a_visiter.pop_front() {
+            if !genantes.contains(&pos) && !game.is_occupied(pos) {
+                let mut chemin = vec![pos];
+                while let Some(&precedent) = precedents.get(chemin.last()?)
+                    && precedent != *chemin.last()?
+                {
+                    chemin.push(precedent);
+                }
+                chemin.reverse();
+                return Some(chemin);
+            }
+            for next in neighbours(map, pos) {
+                if is_walkable(&map[next.1][next.0])
+                    && !occupees.contains(&next)
+                    && !precedents.contains_key(&next)
+                {
+                    precedents.insert(next, pos);
+                    a_visiter.push_back(next);
+                }
+            }
+        }
+        None
+    }
+}
+
+// en actions de robot
+fn etapes(game: &Game, duree: Duration) -> usize {
+    (duree.as_secs_f32()
+        / game
+            .get_config()
+            .robot_cooldown
+            .as_secs_f32()
+            .max(f32::EPSILON)) as usize
+}
+
+// coût supplémentaire pour obtenir `demande` depuis cette case, None si impossible
+fn surcout(game: &Game, demande: &Ingredient, case: &Case) -> Option<usize> {
+    let pret = |ingredient: Ingredient| {
+        if ingredient == *demande {
+            0
+        } else if ingredient.se_cuit() {
+            COUT_PREPARATION + etapes(game, game.get_config().duree_cuisson)
+        } else {
+            COUT_PREPARATION + game.get_config().coups_decoupe as usize
+        }
+    };
+    match case {
+        Case::COUPER(Some(decoupe)) if decoupe.ingredient.peut_devenir(demande) => {
+            Some(decoupe.coups_restants(game.get_config()) as usize)
+        }
+        Case::POELE(Some(cuisson)) if cuisson.ingredient.peut_devenir(demande) => {
+            let fin = cuisson.debut + game.get_config().duree_cuisson;
+            Some(etapes(game, fin.saturating_duration_since(game.now())))
+        }
+        Case::Table(Some(ingredient)) if ingredient.peut_devenir(demande) => {
+            Some(pret(*ingredient))
+        }
+        Case::Ingredient(type_ingredient) if *type_ingredient == demande.type_ingredient => {
+            Some(pret(Ingredient::new(*type_ingredient)))
+        }
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{clock::Clock, config::GameConfig};
+
+    #[test]
+    fn deux_chefs_ne_se_genent_pas_et_livrent() {
+        let config = GameConfig {
+            chefs: 2,
+            ..GameConfig::default()
+        };
+        let mut game = Game::from_map_file("maps/brigade.txt", config)
+            .unwrap()
+            .with_clock(Clock::manual())
+            .with_seed(7);
+        let pas = game.get_config().robot_cooldown;
+        let mut coordinateur = Coordinateur::new();
+
+        // une minute de jeu, bien assez pour une première commande
+        for _ in 0..600 {
+            for id in 0..game.get_players().len() {
+                let _ = game.robot(id, &mut coordinateur);
+            }
+            let [premier, second] = game.get_players().as_slice() else {
+                panic!("deux chefs attendus");
+            };
+            assert_ne!(premier.get_pos(), second.get_pos());
+
+            let tables = coordinateur
+                .get_affectations()
+                .values()
+                .filter(|affectation| affectation.tache == Tache::Ranger)
+                .map(|affectation| affectation.cible)
+                .collect::<Vec<_>>();
+            assert_eq!(
+                tables.len(),
+                tables.iter().collect::<HashSet<_>>().len(),
+                "une table réservée deux fois : {tables:?}"
+            );
+
+            game.advance_clock(pas);
+            game.tick();
+            if game.get_recettes_livrees() > 0 {
+                return;
+            }
+        }
+        panic!("aucune commande livrée");
+    }
+}
@@ -1,18 +1,32 @@
 use crate::{
-    GAME_DURATION, RECETTE_COOLDOWN_RANGE,
-    objets::{Case, Direction, Ingredient, IngredientEtat, IngredientType, Recette},
+    clock::Clock,
+    config::GameConfig,
+    map::{MapError, MapProblem, SPAWN_CHAR, parse_map, validate_map},
+    objets::{Assiette, Case, Cuisson, Decoupe, Direction, Ingredient, Lavage, Objet, Recette},
     player::Player,
+    score::Score,
+    strategy::Strategy,
 };
+use rand::{Rng, SeedableRng, rngs::StdRng};
 use std::{
-    collections::HashSet,
+    fs,
+    path::Path,
     time::{Duration, Instant},
 };
 
+const DEFAULT_MAP: &str = include_str!("../maps/default.txt");
+
 #[derive(Debug, PartialEq)]
 pub enum PickupError {
     HandsFull,
     AssietteEmpty,
     TableEmpty,
+    CouperEmpty,
+    PoeleEmpty,
+    RetourEmpty,
+    EvierEmpty,
+    // l'ingrédient demandé n'est pas sur l'assiette
+    IngredientAbsent,
     NoTarget(((usize, usize), Case)),
 }
 
@@ -20,223 +34,190 @@ pub enum PickupError {
 pub enum DepositError {
     HandsEmpty,
     TableFull,
+    CouperFull,
+    // seuls les ingrédients crus qui se coupent vont sur la planche
+    NotChoppable,
+    PoeleFull,
+    // seule la viande crue va sur la poêle
+    NotCookable,
+    // un ingrédient se pose sur une assiette, pas directement sur l'emplacement
+    NoAssiette,
+    AssietteSale,
+    AssietteFull,
+    // la passe et l'évier ne prennent que des assiettes
+    NeedsAssiette,
+    // les assiettes ne vont ni sur les tables, ni sur les planches, ni sur les poêles
+    AssietteNotAllowed,
+    // une assiette vide n'a rien à jeter
+    NothingToDiscard,
+    // servi à la passe : il manque des ingrédients à une commande
+    PlatIncomplet,
+    // servi à la passe : les bons ingrédients, mais pas empilés dans l'ordre
+    PlatMalEmpile,
+    // servi à la passe : aucune commande ne correspond
+    PlatNonCommande,
+    EvierFull,
     NoTarget(((usize, usize), Case)),
 }
 
-#[derive(Debug)]
+#[derive(Debug, PartialEq)]
+pub enum RobotError {
+    Pickup(PickupError),
+    Deposit(DepositError),
+}
+
+#[derive(Debug, PartialEq, Clone, Copy)]
 pub enum RobotAction {
     Deplacer(Direction),
     Pickup,
     Deposit,
+    // prendre un ingrédient précis sur l'assiette en face
+    Retirer(Ingredient),
     None,
 }
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, PartialEq, Clone)]
 pub struct Game {
-    player: Player,
-    assiette: Vec<Ingredient>,
+    players: Vec<Player>,
     map: Vec<Vec<Case>>,
     recettes: Vec<Recette>,
 
-    score: i32,
+    seed: u64,
+    rng: StdRng,
+    clock: Clock,
+    config: GameConfig,
+
+    score: Score,
+    recettes_livrees: usize,
+    recettes_expirees: usize,
+    premiere_livraison: Option<Duration>,
+    // retour prévu de chaque assiette servie
+    retours: Vec<Instant>,
     next_recette: Instant,
+    start_instant: Instant,
     end_instant: Instant,
     is_finished: bool,
+    // fini avant l'heure, trop de commandes expirées
+    is_perdu: bool,
 }
 
 impl Game {
-    pub fn new() -> Self {
-        let map: Vec<Vec<Case>> = vec![
-            vec![
-                Case::Table(None),
-                Case::Table(None),
-                Case::Table(None),
-                Case::Table(None),
-                Case::Table(None),
-                Case::Table(None),
-                Case::Table(None),
-                Case::Table(None),
-                Case::Table(None),
-                Case::Table(None),
-                Case::Table(None),
-                Case::Table(None),
-                Case::Table(None),
-                Case::ASSIETTE,
-                Case::Table(None),
-            ],
-            vec![
-                Case::Ingredient(IngredientType::Pain),
-                Case::Vide,
-                Case::Vide,
-                Case::Vide,
-                Case::Table(None),
-                Case::Vide,
-                Case::COUPER,
-                Case::Vide,
-                Case::Table(None),
-                Case::Vide,
-                Case::Vide,
-                Case::Vide,
-                Case::Vide,
-                Case::Vide,
-                Case::Table(None),
-            ],
-            vec![
-                Case::Table(None),
-                Case::Vide,
-                Case::Vide,
-                Case::Vide,
-                Case::Table(None),
-                Case::Vide,
-                Case::COUPER,
-                Case::Vide,
-                Case::Table(None),
-                Case::Vide,
-                Case::Vide,
-                Case::Vide,
-                Case::Vide,
-                Case::Vide,
-                Case::Table(None),
-            ],
-            vec![
-                Case::Table(None),
-                Case::Vide,
-                Case::Vide,
-                Case::Vide,
-                Case::Table(None),
-                Case::Vide,
-                Case::COUPER,
-                Case::Vide,
-                Case::Table(None),
-                Case::Vide,
-                Case::Vide,
-                Case::Vide,
-                Case::Vide,
-                Case::Vide,
-                Case::Table(None),
-            ],
-            vec![
-                Case::Table(None),
-                Case::Vide,
-                Case::Vide,
-                Case::Vide,
-                Case::Table(None),
-                Case::Vide,
-                Case::Table(None),
-                Case::Vide,
-                Case::Table(None),
-                Case::Vide,
-                Case::Vide,
-                Case::Table(None),
-                Case::Vide,
-                Case::Vide,
-                Case::Table(None),
-            ],
-            vec![
-                Case::Table(None),
-                Case::Vide,
-                Case::Vide,
-                Case::Vide,
-                Case::Table(None),
-                Case::Vide,
-                Case::Table(None),
-                Case::Vide,
-                Case::Table(None),
-                Case::Vide,
-                Case::Vide,
-                Case::Table(None),
-                Case::Vide,
-                Case::Vide,
-                Case::Table(None),
-            ],
-            vec![
-                Case::Table(None),
-                Case::Vide,
-                Case::Vide,
-                Case::Vide,
-                Case::Table(None),
-                Case::Vide,
-                Case::Vide,
-                Case::Vide,
-                Case::Table(None),
-                Case::Vide,
-                Case::Vide,
-                Case::Table(None),
-                Case::Vide,
-                Case::Vide,
-                Case::Table(None),
-            ],
-            vec![
-                Case::Table(None),
-                Case::Vide,
-                Case::Vide,
-                Case::Vide,
-                Case::Vide,
-                Case::Vide,
-                Case::Vide,
-                Case::Vide,
-                Case::Vide,
-                Case::Vide,
-                Case::Vide,
-                Case::Table(None),
-                Case::Vide,
-                Case::Vide,
-                Case::Table(None),
-            ],
-            vec![
-                Case::Table(None),
-                Case::Vide,
-                Case::Vide,
-                Case::Vide,
-                Case::Table(None),
-                Case::Vide,
-                Case::Vide,
-                Case::Vide,
-                Case::Table(None),
-                Case::Vide,
-                Case::Vide,
-                Case::Table(None),
-                Case::Vide,
-                Case::Vide,
-                Case::Table(None),
-            ],
-            vec![
-                Case::Table(None),
-                Case::Ingredient(IngredientType::Tomate),
-                Case::Table(None),
-                Case::Ingredient(IngredientType::Salade),
-                Case::Table(None),
-                Case::Table(None),
-                Case::Table(None),
-                Case::Table(None),
-                Case::Table(None),
-                Case::Table(None),
-                Case::Table(None),
-                Case::Table(None),
-                Case::Ingredient(IngredientType::Oignon),
-                Case::Table(None),
-                Case::Table(None),
-            ],
-        ];
-
-        Self {
-            player: Player::new((1, 1)),
+    // sur la carte par défaut, qui n'a pas forcément assez de places pour config.chefs
+    pub fn new(config: GameConfig) -> Result<Self, MapError> {
+        Self::from_map_str(DEFAULT_MAP, config)
+    }
+
+    pub fn from_map_str(text: &str, config: GameConfig) -> Result<Self, MapError> {
+        let layout = parse_map(text)?;
+        Self::with_map(layout.map, layout.spawns, config)
+    }
+
+    pub fn from_map_file(path: impl AsRef<Path>, config: GameConfig) -> Result<Self, MapError> {
+        let text = fs::read_to_string(path)?;
+        Self::from_map_str(&text, config)
+    }
+
+    // un chef par point d'apparition, dans l'ordre, jusqu'à config.chefs
+    pub fn with_map(
+        map: Vec<Vec<Case>>,
+        spawns: Vec<(usize, usize)>,
+        config: GameConfig,
+    ) -> Result<Self, MapError> {
+        if spawns.len() < config.chefs {
+            return Err(MapError::Invalid(vec![MapProblem::NotEnoughSpawns {
+                needed: config.chefs,
+                found: spawns.len(),
+            }]));
+        }
+        let spawns = &spawns[..config.chefs];
+        validate_map(&map, spawns, &config.livre.types_requis()).map_err(MapError::Invalid)?;
+
+        let seed = rand::random();
+        let clock = Clock::default();
+        let mut game = Self {
+            players: spawns
+                .iter()
+                .enumerate()
+                .map(|(id, &spawn)| Player::new(id, spawn))
+                .collect(),
             map,
-            recettes: vec![Recette::default_recipe()],
-            assiette: Vec::new(),
-            score: 0,
-            next_recette: Instant::now() + rand::random_range(RECETTE_COOLDOWN_RANGE),
-            end_instant: Instant::now() + GAME_DURATION,
+            recettes: Vec::new(),
+            seed,
+            rng: StdRng::seed_from_u64(seed),
+            clock,
+            config,
+            score: Score::default(),
+            recettes_livrees: 0,
+            recettes_expirees: 0,
+            premiere_livraison: None,
+            retours: Vec::new(),
+            next_recette: clock.now(),
+            start_instant: clock.now(),
+            end_instant: clock.now(),
             is_finished: false,
-        }
+            is_perdu: false,
+        };
+        game.start();
+        Ok(game)
     }
 
-    pub fn get_player(&self) -> &Player {
-        &self.player
+    // même graine + mêmes actions = même partie
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.seed = seed;
+        self.start();
+        self
     }
 
-    pub fn get_assiette(&self) -> &Vec<Ingredient> {
-        &self.assiette
+    pub fn with_clock(mut self, clock: Clock) -> Self {
+        self.clock = clock;
+        self.start();
+        self
+    }
+
+    fn start(&mut self) {
+        let now = self.clock.now();
+        self.rng = StdRng::seed_from_u64(self.seed);
+        self.recettes = vec![Recette::new(now, &mut self.rng, &self.config)];
+        self.next_recette = now + self.rng.random_range(self.config.recette_cooldown.clone());
+        self.start_instant = now;
+        self.end_instant = now + self.config.duree_partie;
+    }
+
+    pub fn get_seed(&self) -> u64 {
+        self.seed
+    }
+
+    pub fn get_players(&self) -> &Vec<Player> {
+        &self.players
+    }
+
+    // les identifiants des joueurs sont leurs indices
+    pub fn get_player(&self, id: usize) -> &Player {
+        &self.players[id]
+    }
+
+    pub fn is_occupied(&self, pos: (usize, usize)) -> bool {
+        self.players.iter().any(|player| player.get_pos() == pos)
+    }
+
+    // les assiettes posées sur les emplacements de dressage
+    pub fn get_assiettes(&self) -> Vec<((usize, usize), Assiette)> {
+        self.map
+            .iter()
+            .enumerate()
+            .flat_map(|(y, row)| {
+                row.iter()
+                    .enumerate()
+                    .filter_map(move |(x, case)| match case {
+                        Case::ASSIETTE(Some(assiette)) => Some(((x, y), *assiette)),
+                        _ => None,
+                    })
+            })
+            .collect()
+    }
+
+    pub fn get_retours(&self) -> &Vec<Instant> {
+        &self.retours
     }
 
     pub fn get_recettes(&self) -> &Vec<Recette> {
@@ -256,31 +237,83 @@ impl Game {
     }
 
     pub fn get_score(&self) -> i32 {
-        self.score
+        self.score.total()
+    }
+
+    pub fn get_detail_score(&self) -> &Score {
+        &self.score
+    }
+
+    pub fn get_recettes_livrees(&self) -> usize {
+        self.recettes_livrees
+    }
+
+    pub fn get_recettes_expirees(&self) -> usize {
+        self.recettes_expirees
+    }
+
+    // temps écoulé entre le début de la partie et la première recette livrée
+    pub fn get_premiere_livraison(&self) -> Option<Duration> {
+        self.premiere_livraison
+    }
+
+    pub fn get_start_instant(&self) -> &Instant {
+        &self.start_instant
+    }
+
+    pub fn get_config(&self) -> &GameConfig {
+        &self.config
     }
 
     pub fn get_end_instant(&self) -> &Instant {
         &self.end_instant
     }
 
+    pub fn get_clock(&self) -> &Clock {
+        &self.clock
+    }
+
+    pub fn now(&self) -> Instant {
+        self.clock.now()
+    }
+
+    // sans effet sur une horloge réelle
+    pub fn advance_clock(&mut self, duration: Duration) {
+        self.clock.advance(duration);
+    }
+
     pub fn get_remaining_time(&self) -> Duration {
-        self.end_instant - Instant::now()
+        self.end_instant.saturating_duration_since(self.clock.now())
     }
 
     pub fn get_percent_left(&self) -> f32 {
-        self.get_remaining_time().as_secs_f32() / GAME_DURATION.as_secs_f32()
+        self.get_remaining_time().as_secs_f32() / self.config.duree_partie.as_secs_f32()
     }
 
     pub fn is_finished(&self) -> bool {
         self.is_finished
     }
 
-    pub fn get_facing(&self, pos: (usize, usize)) -> ((usize, usize), Case) {
+    pub fn is_perdu(&self) -> bool {
+        self.is_perdu
+    }
+
+    // une partie perdue ne rapporte aucune étoile
+    pub fn get_etoiles(&self) -> usize {
+        if self.is_perdu {
+            return 0;
+        }
+        self.config.etoiles(self.get_score())
+    }
+
+    pub fn get_facing(&self, id: usize) -> ((usize, usize), Case) {
+        let player = &self.players[id];
+        let pos = player.get_pos();
         let mut facing_pos: (usize, usize) = pos;
         let lenx: usize = self.map[0].len();
         let leny: usize = self.map.len();
 
-        match self.player.get_facing() {
+        match player.get_facing() {
             Direction::North => facing_pos.1 = pos.1 - 1,
             Direction::West => facing_pos.0 = pos.0 - 1,
             Direction::South => facing_pos.1 = pos.1 + 1,
@@ -294,130 +327,304 @@ impl Game {
         (facing_pos, self.map[facing_pos.1][facing_pos.0])
     }
 
-    fn get_neighbours(&self, x: usize, y: usize) -> Vec<(usize, usize)> {
-        let mut neighbours = Vec::new();
-        if x > 0 {
-            neighbours.push((x - 1, y));
-        }
-        if y > 0 {
-            neighbours.push((x, y - 1));
-        }
-        if x < self.map[0].len() - 1 {
-            neighbours.push((x + 1, y));
-        }
-        if y < self.map.len() - 1 {
-            neighbours.push((x, y + 1));
-        }
-        neighbours
-    }
-
     fn add_random_recette(&mut self, now: Instant) {
-        self.recettes.push(Recette::new(now));
+        self.recettes
+            .push(Recette::new(now, &mut self.rng, &self.config));
         self.recettes.sort_by_key(|r| *r.get_expiration());
     }
 
-    fn move_player(&mut self, direction: Direction) {
-        self.player.set_facing(direction);
-        let wanted_pos: (usize, usize) = self.get_facing(self.player.get_pos()).0;
-        if self.map[wanted_pos.1][wanted_pos.0] == Case::Vide {
-            self.player.set_pos(wanted_pos.0, wanted_pos.1, direction);
+    // deux chefs ne peuvent pas être sur la même case
+    pub fn move_player(&mut self, id: usize, direction: Direction) {
+        if self.is_finished {
+            return;
+        }
+
+        self.players[id].set_facing(direction);
+        let (wanted_pos, wanted_case) = self.get_facing(id);
+        if wanted_case == Case::Vide && !self.is_occupied(wanted_pos) {
+            self.players[id].set_pos(wanted_pos.0, wanted_pos.1, direction);
         }
     }
 
-    pub fn pickup(&mut self) -> Result<(), PickupError> {
+    // prend si les mains sont vides, pose sinon
+    pub fn interact(&mut self, id: usize) -> Result<(), RobotError> {
+        if self.players[id].get_object_held().is_none() {
+            self.pickup(id).map_err(RobotError::Pickup)
+        } else {
+            self.deposit(id).map_err(RobotError::Deposit)
+        }
+    }
+
+    pub fn pickup(&mut self, id: usize) -> Result<(), PickupError> {
         if self.is_finished {
             return Ok(());
         }
 
-        let (facing_pos, facing_object) = self.get_facing(self.player.get_pos());
-        if self.player.get_object_held().is_some() {
+        let (facing_pos, facing_object) = self.get_facing(id);
+        let player = &mut self.players[id];
+        if player.get_object_held().is_some() {
             return Err(PickupError::HandsFull);
         }
 
         match facing_object {
-            Case::ASSIETTE => {
-                if let Some(ingredient) = self.assiette.pop() {
-                    self.player.set_object_held(Some(ingredient));
-                } else {
-                    return Err(PickupError::AssietteEmpty);
-                }
+            Case::ASSIETTE(None) => return Err(PickupError::AssietteEmpty),
+            Case::ASSIETTE(Some(assiette)) => {
+                player.set_object_held(Some(Objet::Assiette(assiette)));
+                self.map[facing_pos.1][facing_pos.0] = Case::ASSIETTE(None);
+            }
+            Case::Ingredient(object) => {
+                player.set_object_held(Some(Objet::Ingredient(Ingredient::new(object))))
             }
-            Case::Ingredient(object) => self.player.set_object_held(Some(Ingredient::new(object))),
             Case::Table(None) => return Err(PickupError::TableEmpty),
-            Case::Table(ingredient) => {
-                self.player.set_object_held(ingredient);
+            Case::Table(Some(ingredient)) => {
+                player.set_object_held(Some(Objet::Ingredient(ingredient)));
                 self.map[facing_pos.1][facing_pos.0] = Case::Table(None);
             }
+            Case::COUPER(None) => return Err(PickupError::CouperEmpty),
+            // tant que ce n'est pas coupé, interagir donne un coup de couteau
+            Case::COUPER(Some(mut decoupe)) if !decoupe.is_pret() => {
+                decoupe.avancer(&self.config);
+                self.map[facing_pos.1][facing_pos.0] = Case::COUPER(Some(decoupe));
+            }
+            Case::COUPER(Some(decoupe)) => {
+                player.set_object_held(Some(Objet::Ingredient(decoupe.ingredient)));
+                self.map[facing_pos.1][facing_pos.0] = Case::COUPER(None);
+            }
+            Case::POELE(None) => return Err(PickupError::PoeleEmpty),
+            // retirer la poêle du feu arrête la cuisson, même si ce n'est pas prêt
+            Case::POELE(Some(cuisson)) => {
+                player.set_object_held(Some(Objet::Ingredient(cuisson.ingredient)));
+                self.map[facing_pos.1][facing_pos.0] = Case::POELE(None);
+            }
+            Case::RETOUR(0) => return Err(PickupError::RetourEmpty),
+            Case::RETOUR(sales) => {
+                player.set_object_held(Some(Objet::Assiette(Assiette::sale())));
+                self.map[facing_pos.1][facing_pos.0] = Case::RETOUR(sales - 1);
+            }
+            Case::EVIER(None) => return Err(PickupError::EvierEmpty),
+            // tant qu'elle n'est pas propre, interagir donne un coup d'éponge
+            Case::EVIER(Some(mut lavage)) if !lavage.is_propre(&self.config) => {
+                lavage.avancer();
+                self.map[facing_pos.1][facing_pos.0] = Case::EVIER(Some(lavage));
+            }
+            Case::EVIER(Some(_)) => {
+                player.set_object_held(Some(Objet::Assiette(Assiette::default())));
+                self.map[facing_pos.1][facing_pos.0] = Case::EVIER(None);
+            }
             _ => return Err(PickupError::NoTarget((facing_pos, facing_object))),
         }
 
         Ok(())
     }
 
-    pub fn deposit(&mut self) -> Result<(), DepositError> {
+    // prend sur l'assiette en face un ingrédient précis, où qu'il soit dans la pile
+    pub fn retirer(&mut self, id: usize, ingredient: Ingredient) -> Result<(), PickupError> {
         if self.is_finished {
             return Ok(());
         }
 
-        let (facing_pos, facing_object) = self.get_facing(self.player.get_pos());
-        let object_held = match self.player.take_object_held() {
+        let (facing_pos, facing_object) = self.get_facing(id);
+        if self.players[id].get_object_held().is_some() {
+            return Err(PickupError::HandsFull);
+        }
+        let mut assiette = match facing_object {
+            Case::ASSIETTE(Some(assiette)) => assiette,
+            Case::ASSIETTE(None) => return Err(PickupError::AssietteEmpty),
+            _ => return Err(PickupError::NoTarget((facing_pos, facing_object))),
+        };
+        if !assiette.retirer(&ingredient) {
+            return Err(PickupError::IngredientAbsent);
+        }
+        self.map[facing_pos.1][facing_pos.0] = Case::ASSIETTE(Some(assiette));
+        self.players[id].set_object_held(Some(Objet::Ingredient(ingredient)));
+        Ok(())
+    }
+
+    pub fn deposit(&mut self, id: usize) -> Result<(), DepositError> {
+        if self.is_finished {
+            return Ok(());
+        }
+
+        let (facing_pos, facing_object) = self.get_facing(id);
+        let object_held = match self.players[id].get_object_held() {
             None => return Err(DepositError::HandsEmpty),
             Some(obj) => obj,
         };
 
-        match facing_object {
-            Case::ASSIETTE => {
-                self.assiette.push(object_held);
+        // en cas d'erreur le chef garde ce qu'il a en main
+        let new_object_held = match (object_held, facing_object) {
+            (Objet::Ingredient(_), Case::ASSIETTE(None)) => return Err(DepositError::NoAssiette),
+            (Objet::Ingredient(ingredient), Case::ASSIETTE(Some(mut assiette))) => {
+                if assiette.sale {
+                    return Err(DepositError::AssietteSale);
+                }
+                if !assiette.ajouter(ingredient) {
+                    return Err(DepositError::AssietteFull);
+                }
+                self.map[facing_pos.1][facing_pos.0] = Case::ASSIETTE(Some(assiette));
+                None
+            }
+            (Objet::Assiette(assiette), Case::ASSIETTE(None)) => {
+                self.map[facing_pos.1][facing_pos.0] = Case::ASSIETTE(Some(assiette));
+                None
+            }
+            (Objet::Assiette(_), Case::ASSIETTE(Some(_))) => {
+                return Err(DepositError::AssietteFull);
+            }
+            (Objet::Ingredient(ingredient), Case::Table(None)) => {
+                self.map[facing_pos.1][facing_pos.0] = Case::Table(Some(ingredient));
+                None
+            }
+            (_, Case::Table(Some(_))) => return Err(DepositError::TableFull),
+            (Objet::Ingredient(ingredient), Case::COUPER(None)) if ingredient.se_coupe() => {
+                let decoupe = Decoupe::new(ingredient);
+                self.map[facing_pos.1][facing_pos.0] = Case::COUPER(Some(decoupe));
+                None
+            }
+            (Objet::Ingredient(_), Case::COUPER(None)) => return Err(DepositError::NotChoppable),
+            (_, Case::COUPER(Some(_))) => return Err(DepositError::CouperFull),
+            (Objet::Ingredient(ingredient), Case::POELE(None)) if ingredient.se_cuit() => {
+                let cuisson = Cuisson::new(ingredient, self.clock.now());
+                self.map[facing_pos.1][facing_pos.0] = Case::POELE(Some(cuisson));
+                None
+            }
+            (Objet::Ingredient(_), Case::POELE(None)) => return Err(DepositError::NotCookable),
+            (_, Case::POELE(Some(_))) => return Err(DepositError::PoeleFull),
+            (Objet::Assiette(Assiette { sale: true, .. }), Case::PASSE) => {
+                return Err(DepositError::AssietteSale);
+            }
+            (Objet::Assiette(assiette), Case::PASSE) => {
+                self.servir(&assiette)?;
+                None
+            }
+            // ce qu'il restait dans l'assiette part avec l'eau de vaisselle
+            (Objet::Assiette(_), Case::EVIER(None)) => {
+                self.map[facing_pos.1][facing_pos.0] = Case::EVIER(Some(Lavage::default()));
+                None
+            }
+            (Objet::Assiette(_), Case::EVIER(Some(_))) => return Err(DepositError::EvierFull),
+            (Objet::Ingredient(_), Case::POUBELLE) => {
+                self.score.jeter(1, &self.config);
+                None
             }
-            Case::Table(None) => {
-                self.map[facing_pos.1][facing_pos.0] = Case::Table(Some(object_held));
+            (Objet::Assiette(assiette), Case::POUBELLE) if assiette.is_empty() => {
+                return Err(DepositError::NothingToDiscard);
             }
-            Case::Table(_) => return Err(DepositError::TableFull),
-            Case::COUPER => {
-                let mut ingredient = object_held;
-                ingredient.couper();
-                self.player.set_object_held(Some(ingredient));
+            // le chef garde l'assiette, vidée
+            (Objet::Assiette(mut assiette), Case::POUBELLE) => {
+                self.score
+                    .jeter(assiette.get_ingredients().len(), &self.config);
+                assiette.vider();
+                Some(Objet::Assiette(assiette))
+            }
+            (Objet::Ingredient(_), Case::PASSE | Case::EVIER(_)) => {
+                return Err(DepositError::NeedsAssiette);
+            }
+            (Objet::Assiette(_), Case::Table(None) | Case::COUPER(None) | Case::POELE(None)) => {
+                return Err(DepositError::AssietteNotAllowed);
             }
             _ => return Err(DepositError::NoTarget((facing_pos, facing_object))),
-        }
+        };
+        self.players[id].set_object_held(new_object_held);
 
         Ok(())
     }
 
-    pub fn tick(&mut self, now: Instant) {
+    // livre l'assiette à la commande en cours qui lui correspond exactement
+    fn servir(&mut self, assiette: &Assiette) -> Result<(), DepositError> {
+        let now = self.clock.now();
+        let contenu = assiette.contenu();
+        let mut en_cours = self
+            .recettes
+            .iter()
+            .enumerate()
+            .filter(|(_, recette)| !recette.is_too_late(now));
+        let Some((i, _)) = en_cours
+            .clone()
+            .find(|(_, recette)| recette.accepte(assiette))
+        else {
+            // tous les ingrédients d'une commande, mais dans le désordre
+            if en_cours
+                .clone()
+                .any(|(_, recette)| contenu == *recette.get_ingredients())
+            {
+                return Err(DepositError::PlatMalEmpile);
+            }
+            if en_cours.any(|(_, recette)| contenu.is_subset(recette.get_ingredients())) {
+                return Err(DepositError::PlatIncomplet);
+            }
+            return Err(DepositError::PlatNonCommande);
+        };
+
+        let recette = self.recettes.remove(i);
+        self.score.livrer(
+            &recette,
+            recette.get_percent_left(now),
+            recette.is_ordre_exact(assiette),
+            &self.config,
+        );
+        self.recettes_livrees += 1;
+        self.premiere_livraison
+            .get_or_insert(now.saturating_duration_since(self.start_instant));
+        self.retours.push(now + self.config.delai_retour_assiette);
+        Ok(())
+    }
+
+    pub fn tick(&mut self) {
         if self.is_finished {
             return;
         }
 
-        let (recettes_too_late, mut new_recettes): (Vec<_>, Vec<_>) = self
+        let now = self.clock.now();
+        for case in self.map.iter_mut().flatten() {
+            if let Case::POELE(Some(cuisson)) = case {
+                cuisson.avancer(now, &self.config);
+            }
+        }
+
+        let (recettes_too_late, new_recettes): (Vec<_>, Vec<_>) = self
             .recettes
             .clone()
             .into_iter()
             .partition::<Vec<_>, _>(|recette| recette.is_too_late(now));
 
-        let assiette_hashset = self.assiette.clone().into_iter().collect::<HashSet<_>>();
-        let recette_correspondante = self
-            .recettes
-            .iter()
-            .position(|recette| assiette_hashset.eq(recette.get_ingredients()));
-        if let Some(i) = recette_correspondante {
-            let bonus = self.assiette.len() as i32 * 2;
-            self.score += bonus;
-            self.assiette.clear();
-            new_recettes.remove(i);
+        // les assiettes servies reviennent sales sur le premier retour
+        let (arrivees, retours): (Vec<_>, Vec<_>) =
+            self.retours.iter().partition(|&&retour| retour <= now);
+        self.retours = retours;
+        if let Some(Case::RETOUR(sales)) = self
+            .map
+            .iter_mut()
+            .flatten()
+            .find(|case| matches!(case, Case::RETOUR(_)))
+        {
+            *sales += arrivees.len();
         }
 
         for recette in &recettes_too_late {
-            self.score -= (IngredientType::iter().len() - recette.get_ingredients().len()) as i32
+            self.score.expirer(recette, &self.config);
         }
-
+        self.recettes_expirees += recettes_too_late.len();
         // update the too lates recettes
         self.recettes = new_recettes;
-        if self.next_recette <= now || self.recettes.len() < 2 {
+        if self
+            .config
+            .expirations_max
+            .is_some_and(|max| self.recettes_expirees >= max)
+        {
+            self.is_finished = true;
+            self.is_perdu = true;
+            return;
+        }
+
+        let manque_recettes = self.recettes.len() < self.config.recettes_min;
+        if (self.next_recette <= now || manque_recettes)
+            && self.config.peut_ajouter_recette(self.recettes.len())
+        {
             self.add_random_recette(now);
-            if self.next_recette <= now {
-                self.next_recette = now + rand::random_range(RECETTE_COOLDOWN_RANGE);
-            }
+        }
+        if self.next_recette <= now {
+            self.next_recette = now + self.rng.random_range(self.config.recette_cooldown.clone());
         }
 
         if self.end_instant <= now {
@@ -425,246 +632,32 @@ impl Game {
         }
     }
 
-    pub fn robot(&mut self) {
+    pub fn robot(&mut self, id: usize, strategy: &mut dyn Strategy) -> Result<(), RobotError> {
         if self.is_finished {
-            return;
-        }
-
-        let action = self.determine_action();
-        match action {
-            RobotAction::Deplacer(direction) => self.move_player(direction),
-            RobotAction::Pickup => self.pickup().expect("Failed to pick up ingredient"),
-            RobotAction::Deposit => self.deposit().expect("Failed to deposit ingredient"),
-            RobotAction::None => (),
-        }
-    }
-
-    fn determine_action(&self) -> RobotAction {
-        let objectives = self.determine_objectives();
-        let (x, y) = self.player.get_pos();
-
-        for objective_level in objectives {
-            // parmis un niveau d'objectif, choisir celui le plus proche
-            let mut choosen_path: Vec<(usize, usize)> = Vec::new();
-            let mut choosen_dist: usize = usize::MAX;
-            for objective in objective_level {
-                match self.pathfind_case((x, y), objective) {
-                    Some(chemin) => {
-                        if chemin.len() < choosen_dist {
-                            choosen_dist = chemin.len();
-                            choosen_path = chemin;
-                        }
-                    }
-                    _ => continue,
-                };
-            }
-            if choosen_dist == usize::MAX {
-                continue;
-            }
-
-            let next_pos = match choosen_path.get(1) {
-                Some(value) => *value,
-                None => continue,
-            };
-
-            let direction = match next_pos {
-                (x1, y1) if (x1, y1) == (x, y - 1) => Direction::North,
-                (x1, y1) if (x1, y1) == (x, y + 1) => Direction::South,
-                (x1, y1) if (x1, y1) == (x - 1, y) => Direction::West,
-                (x1, y1) if (x1, y1) == (x + 1, y) => Direction::East,
-                _ => continue,
-            };
-
-            if choosen_path.len() != 2 || self.player.get_facing() != direction {
-                return RobotAction::Deplacer(direction);
-            }
-
-            if self.player.get_object_held().is_none() {
-                return RobotAction::Pickup;
-            } else {
-                return RobotAction::Deposit;
-            }
+            return Ok(());
         }
 
-        RobotAction::None
+        let action = strategy.next_action(self, id);
+        self.apply_action(id, action)
     }
 
-    fn determine_objectives(&self) -> Vec<Vec<Case>> {
-        let assiette_hashset = self.assiette.clone().into_iter().collect::<HashSet<_>>();
-
-        let mut diff = usize::MAX;
-        let mut assiette_priv_recette: HashSet<Ingredient> = HashSet::new();
-        let mut recette_priv_assiette: HashSet<Ingredient> = HashSet::new();
-        let mut recette_hashset: HashSet<Ingredient> = HashSet::new();
-
-        for recette in self.recettes.iter() {
-            let current_recette_priv_assiette = recette
-                .get_ingredients()
-                .difference(&assiette_hashset)
-                .cloned()
-                .collect::<HashSet<_>>();
-            let current_assiette_priv_recette = assiette_hashset
-                .difference(recette.get_ingredients())
-                .cloned()
-                .collect::<HashSet<_>>();
-            let current_diff =
-                current_assiette_priv_recette.len() + current_recette_priv_assiette.len();
-            if current_diff < diff {
-                diff = current_diff;
-                assiette_priv_recette = current_assiette_priv_recette;
-                recette_priv_assiette = current_recette_priv_assiette;
-                recette_hashset = recette.get_ingredients().clone();
-            }
-        }
-
-        if diff == usize::MAX {
-            return vec![];
-        }
-
-        if !assiette_priv_recette.is_empty() {
-            if let Some(held_ingredient) = self.player.get_object_held()
-                && !recette_hashset.contains(&held_ingredient)
-            {
-                return vec![vec![Case::Table(None)]];
-            }
-            return vec![vec![Case::ASSIETTE]];
-        } else if recette_priv_assiette.is_empty() {
-            return vec![];
-        }
-
-        if let Some(held_ingredient) = self.player.get_object_held() {
-            if recette_priv_assiette.contains(&held_ingredient) {
-                return vec![vec![Case::ASSIETTE]];
-            } else if recette_priv_assiette
-                .iter()
-                .any(|ingr| held_ingredient.type_ingredient.eq(&ingr.type_ingredient))
-            {
-                return vec![vec![Case::COUPER]];
-            } else {
-                return vec![vec![Case::Table(None)]];
-            }
-        }
-
-        let mut recette_priv_assiette_vec = recette_priv_assiette.into_iter().collect::<Vec<_>>();
-
-        // choisit l'ingredient qui apparait le plus dans les recettes d'apres (au cas où la recette actuelle se termine)
-        recette_priv_assiette_vec.sort_by(|ingr1, ingr2| {
-            let ingr1_count = self
-                .recettes
-                .iter()
-                .filter(|recette| {
-                    recette
-                        .get_ingredients()
-                        .iter()
-                        .collect::<HashSet<_>>()
-                        .contains(ingr1)
-                })
-                .count();
-            let ingr2_count = self
-                .recettes
-                .iter()
-                .filter(|recette| {
-                    recette
-                        .get_ingredients()
-                        .iter()
-                        .collect::<HashSet<_>>()
-                        .contains(ingr2)
-                })
-                .count();
-            // count par ordre décroissant et ingrédients par ordre croissant
-            ingr2_count.cmp(&ingr1_count).then(ingr1.cmp(ingr2))
-        });
-
-        let next_ingredient = recette_priv_assiette_vec.first().unwrap();
-        vec![
-            vec![Case::Table(Some(Ingredient {
-                // priorité à lui
-                type_ingredient: next_ingredient.type_ingredient,
-                etat: IngredientEtat::Coupe,
-            }))],
-            vec![
-                // sinon le plus proche d'eux
-                Case::Table(Some(Ingredient {
-                    type_ingredient: next_ingredient.type_ingredient,
-                    etat: IngredientEtat::Normal,
-                })),
-                Case::Ingredient(next_ingredient.type_ingredient),
-            ],
-        ]
-    }
-
-    fn pathfind_case(&self, start: (usize, usize), case: Case) -> Option<Vec<(usize, usize)>> {
-        let mut weights: Vec<Vec<usize>> =
-            vec![vec![usize::MAX; self.map[0].len()]; self.map.len()];
-        let mut explored_positions: HashSet<(usize, usize)> = HashSet::new();
-        let mut next_positions: Vec<(usize, usize)> = vec![start];
-        weights[start.1][start.0] = 0;
-
-        let mut found_pos: Option<(usize, usize)> = None;
-        while let Some((x, y)) = next_positions.pop() {
-            if !explored_positions.insert((x, y)) {
-                continue;
-            }
-
-            let mut min_neighbour = usize::MAX;
-            for (x1, y1) in self.get_neighbours(x, y) {
-                if weights[y1][x1] == usize::MAX {
-                    if self.map[y1][x1] == Case::Vide {
-                        next_positions.insert(0, (x1, y1));
-                    } else if self.map[y1][x1] == case {
-                        found_pos = Some((x1, y1));
-                    }
-                } else if weights[y1][x1] < min_neighbour {
-                    min_neighbour = weights[y1][x1];
-                }
-            }
-
-            if min_neighbour != usize::MAX {
-                weights[y][x] = min_neighbour + 1;
-            }
-
-            if found_pos.is_some() {
-                break;
-            }
-        }
-
-        found_pos?;
-        let mut path = vec![found_pos.unwrap()];
-
-        loop {
-            let (x, y) = match path.first().cloned() {
-                None => break,
-                Some(pos) => {
-                    if pos == start {
-                        break;
-                    } else {
-                        pos
-                    }
-                }
-            };
-
-            let mut min_x = 0;
-            let mut min_y = 0;
-            let mut min_val = usize::MAX;
-            for (x1, y1) in self.get_neighbours(x, y) {
-                if weights[y1][x1] < min_val {
-                    min_x = x1;
-                    min_y = y1;
-                    min_val = weights[y1][x1];
-                }
-            }
-            if min_val != usize::MAX {
-                path.insert(0, (min_x, min_y));
+    pub fn apply_action(&mut self, id: usize, action: RobotAction) -> Result<(), RobotError> {
+        match action {
+            RobotAction::Deplacer(direction) => self.move_player(id, direction),
+            RobotAction::Pickup => self.pickup(id).map_err(RobotError::Pickup)?,
+            RobotAction::Deposit => self.deposit(id).map_err(RobotError::Deposit)?,
+            RobotAction::Retirer(ingredient) => {
+                self.retirer(id, ingredient).map_err(RobotError::Pickup)?
             }
+            RobotAction::None => (),
         }
-
-        Some(path)
+        Ok(())
     }
 }
 
 impl Default for Game {
     fn default() -> Self {
-        Self::new()
+        Self::new(GameConfig::default()).expect("La carte par défaut est invalide")
     }
 }
 
@@ -676,8 +669,8 @@ impl std::fmt::Display for Game {
                 .enumerate()
                 .map(|(x, case)| match case {
                     Case::Vide => {
-                        if (x, y) == self.player.get_pos() {
-                            "·".to_string()
+                        if self.is_occupied((x, y)) {
+                            SPAWN_CHAR.to_string()
                         } else {
                             " ".to_string()
                         }
@@ -685,8 +678,13 @@ impl std::fmt::Display for Game {
                     Case::Table(None) => "#".to_string(),
                     Case::Table(Some(ingredient)) => ingredient.type_ingredient.char().to_string(),
                     Case::Ingredient(ingredient_type) => ingredient_type.upper_char().to_string(),
-                    Case::COUPER => "C".to_string(),
-                    Case::ASSIETTE => "O".to_string(),
+                    Case::COUPER(_) => "C".to_string(),
+                    Case::ASSIETTE(_) => "A".to_string(),
+                    Case::POELE(_) => "F".to_string(),
+                    Case::PASSE => "H".to_string(),
+                    Case::RETOUR(_) => "R".to_string(),
+                    Case::EVIER(_) => "E".to_string(),
+                    Case::POUBELLE => "X".to_string(),
                 })
                 .collect::<Vec<_>>()
                 .join(" ");
@@ -695,22 +693,413 @@ impl std::fmt::Display for Game {
         }
         writeln!(f)?;
 
+        let now = self.clock.now();
         let line = self
             .recettes
             .iter()
-            .map(Recette::to_string)
+            .map(|recette| {
+                let temps = recette.get_temps_restant(now).as_secs_f32();
+                format!("{temps:.2}s restantes sur {recette}")
+            })
             .collect::<Vec<_>>()
             .join("\n");
         writeln!(f, "Recettes voulues : {}", line)?;
 
-        let line = self
-            .assiette
-            .iter()
-            .map(Ingredient::to_string)
-            .collect::<Vec<_>>()
-            .join(", ");
-        writeln!(f, "Assiette : {line}")?;
+        for (pos, assiette) in self.get_assiettes() {
+            let line = assiette
+                .get_ingredients()
+                .iter()
+                .map(Ingredient::to_string)
+                .collect::<Vec<_>>()
+                .join(", ");
+            writeln!(f, "Assiette en {pos:?} : {line}")?;
+        }
 
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::objets::{IngredientEtat, IngredientType};
+    use Direction::{East, North, South, West};
+    use RobotAction::{Deplacer, Deposit, Pickup};
+
+    // le chef apparaît en (1, 1), dans un couloir qui longe tous les postes :
+    // assiette, planche, poêle, passe et retour au nord, pain à l'ouest, évier à l'est,
+    // salade, poubelle et viande au sud
+    const CUISINE: &str = "#ACFHR#\nP·    E\n#S#X#V#";
+    const TARTINE: &str = "[Tartine]\ningredients = pain, salade";
+    const MENU: &str = "[Tartine]\ningredients = pain, salade\n[Steak]\ningredients = pain | viande | pain\n[Verdure]\ningredients = salade, salade";
+
+    const PAS: Duration = Duration::from_millis(100);
+
+    fn config(livre: &str) -> GameConfig {
+        GameConfig {
+            livre: livre.parse().unwrap(),
+            ..GameConfig::default()
+        }
+    }
+
+    fn partie(config: GameConfig, horloge: Clock, seed: u64) -> Game {
+        Game::from_map_str(CUISINE, config)
+            .unwrap()
+            .with_clock(horloge)
+            .with_seed(seed)
+    }
+
+    // chaque action est suivie d'un tick, un pas de temps plus tard
+    fn jouer(game: &mut Game, actions: &[RobotAction]) {
+        for &action in actions {
+            game.apply_action(0, action).unwrap();
+            game.advance_clock(PAS);
+            game.tick();
+        }
+    }
+
+    // place le chef devant un poste, un objet en main
+    fn placer(game: &mut Game, (x, y): (usize, usize), direction: Direction, objet: Option<Objet>) {
+        game.players[0].set_pos(x, y, direction);
+        game.players[0].set_object_held(objet);
+    }
+
+    fn pret(type_ingredient: IngredientType) -> Ingredient {
+        Ingredient {
+            type_ingredient,
+            etat: type_ingredient.etat_pret(),
+        }
+    }
+
+    // une assiette propre garnie d'ingrédients prêts, dans l'ordre donné
+    fn assiette(types: &[IngredientType]) -> Assiette {
+        let mut assiette = Assiette::default();
+        for &type_ingredient in types {
+            assiette.ajouter(pret(type_ingredient));
+        }
+        assiette
+    }
+
+    fn attendre(game: &mut Game, duree: Duration) {
+        let fin = game.now() + duree;
+        while game.now() < fin {
+            game.advance_clock(PAS);
+            game.tick();
+        }
+    }
+
+    // coupe le pain puis la salade, les pose sur l'assiette et sert le tout
+    fn tartine(config: &GameConfig) -> Vec<RobotAction> {
+        let mut actions = Vec::new();
+        for caisse in [West, South] {
+            actions.extend([
+                Deplacer(caisse),
+                Pickup,
+                Deplacer(East),
+                Deplacer(North),
+                Deposit,
+            ]);
+            // les coups de couteau, puis on reprend l'ingrédient coupé
+            actions.extend((0..=config.coups_decoupe).map(|_| Pickup));
+            actions.extend([Deplacer(West), Deplacer(North), Deposit]);
+        }
+        actions.extend([
+            Pickup,
+            Deplacer(East),
+            Deplacer(East),
+            Deplacer(East),
+            Deplacer(North),
+            Deposit,
+        ]);
+        actions
+    }
+
+    #[test]
+    fn meme_graine_et_memes_actions_donnent_la_meme_partie() {
+        let horloge = Clock::manual();
+        let mut parties = [7, 7].map(|seed| partie(config(TARTINE), horloge, seed));
+        for game in &mut parties {
+            let actions = tartine(game.get_config());
+            jouer(game, &actions);
+            attendre(game, Duration::from_secs(60));
+        }
+
+        let [a, b] = &parties;
+        assert_eq!(a.get_recettes_livrees(), 1);
+        assert_eq!(a.get_recettes(), b.get_recettes());
+        assert_eq!(a.get_detail_score(), b.get_detail_score());
+        assert_eq!(a.get_map(), b.get_map());
+    }
+
+    #[test]
+    fn une_autre_graine_tire_d_autres_commandes() {
+        let horloge = Clock::manual();
+        let commandes = |seed| {
+            let mut game = partie(config(MENU), horloge, seed);
+            attendre(&mut game, Duration::from_secs(30));
+            game.get_recettes().clone()
+        };
+        assert_eq!(commandes(7), commandes(7));
+        assert_ne!(commandes(7), commandes(8));
+    }
+
+    #[test]
+    fn le_temps_restant_suit_l_horloge_manuelle() {
+        let mut game = partie(config(TARTINE), Clock::manual(), 0);
+        let duree = game.get_config().duree_partie;
+        assert_eq!(game.get_remaining_time(), duree);
+        assert_eq!(game.get_percent_left(), 1.);
+
+        game.advance_clock(duree / 4);
+        game.tick();
+        assert_eq!(game.get_remaining_time(), duree * 3 / 4);
+        assert_eq!(game.get_percent_left(), 0.75);
+    }
+
+    #[test]
+    fn une_commande_expire_a_son_echeance() {
+        let mut game = partie(config(TARTINE), Clock::manual(), 0);
+        let commande = game.get_recettes()[0].clone();
+        let echeance = *commande.get_expiration();
+
+        game.advance_clock(echeance - game.now() - Duration::from_millis(1));
+        game.tick();
+        assert!(game.get_recettes().contains(&commande));
+        assert_eq!(game.get_recettes_expirees(), 0);
+
+        game.advance_clock(Duration::from_millis(1));
+        game.tick();
+        assert!(!game.get_recettes().contains(&commande));
+        assert_eq!(game.get_recettes_expirees(), 1);
+    }
+
+    #[test]
+    fn la_partie_finit_a_l_heure() {
+        let mut game = partie(config(TARTINE), Clock::manual(), 0);
+        let duree = game.get_config().duree_partie;
+
+        game.advance_clock(duree - Duration::from_millis(1));
+        game.tick();
+        assert!(!game.is_finished());
+
+        game.advance_clock(Duration::from_millis(1));
+        game.tick();
+        assert!(game.is_finished());
+        assert_eq!(game.get_remaining_time(), Duration::ZERO);
+        assert_eq!(game.get_percent_left(), 0.);
+    }
+
+    #[test]
+    fn la_viande_cuit_puis_brule_et_ne_se_sert_plus() {
+        let mut game = partie(
+            config("[Steak]\ningredients = viande\ndelai = [60, 60]"),
+            Clock::manual(),
+            0,
+        );
+        let config = game.get_config().clone();
+        let viande = Ingredient::new(IngredientType::Viande);
+        let etat = |game: &Game| match game.map[0][3] {
+            Case::POELE(Some(cuisson)) => cuisson.ingredient.etat,
+            case => panic!("viande attendue sur la poêle, trouvé {case:?}"),
+        };
+
+        placer(&mut game, (3, 1), North, Some(Objet::Ingredient(viande)));
+        game.deposit(0).unwrap();
+        attendre(&mut game, config.duree_cuisson - PAS);
+        assert_eq!(etat(&game), IngredientEtat::Normal);
+        attendre(&mut game, PAS);
+        assert_eq!(etat(&game), IngredientEtat::Cuit);
+        attendre(&mut game, config.delai_brulure - PAS);
+        assert_eq!(etat(&game), IngredientEtat::Cuit);
+        attendre(&mut game, PAS);
+        assert_eq!(etat(&game), IngredientEtat::Brule);
+
+        // la viande brûlée tient sur l'assiette, mais aucune commande n'en veut
+        game.pickup(0).unwrap();
+        let brulee = game.players[0].get_object_held();
+        placer(&mut game, (1, 1), North, brulee);
+        game.deposit(0).unwrap();
+        game.pickup(0).unwrap();
+        let Some(Objet::Assiette(assiette)) = game.players[0].get_object_held() else {
+            panic!("assiette attendue en main");
+        };
+        assert!(
+            game.get_recettes()
+                .iter()
+                .all(|recette| !recette.peut_completer(&assiette))
+        );
+        placer(&mut game, (4, 1), North, Some(Objet::Assiette(assiette)));
+        assert_eq!(game.deposit(0), Err(DepositError::PlatNonCommande));
+    }
+
+    #[test]
+    fn l_ingredient_reste_sur_la_planche_jusqu_au_dernier_coup() {
+        let mut game = partie(config(TARTINE), Clock::manual(), 0);
+        let coups_decoupe = game.get_config().coups_decoupe;
+        let pain = Ingredient::new(IngredientType::Pain);
+        let decoupe = |game: &Game| match game.map[0][2] {
+            Case::COUPER(Some(decoupe)) => decoupe,
+            case => panic!("pain attendu sur la planche, trouvé {case:?}"),
+        };
+
+        placer(&mut game, (2, 1), North, Some(Objet::Ingredient(pain)));
+        game.deposit(0).unwrap();
+        let salade = Objet::Ingredient(Ingredient::new(IngredientType::Salade));
+        placer(&mut game, (2, 1), North, Some(salade));
+        assert_eq!(game.deposit(0), Err(DepositError::CouperFull));
+        placer(&mut game, (2, 1), North, None);
+
+        for coups in 1..=coups_decoupe {
+            game.pickup(0).unwrap();
+            assert_eq!(game.players[0].get_object_held(), None);
+            assert_eq!(decoupe(&game).coups, coups);
+            let etat = if coups < coups_decoupe {
+                IngredientEtat::Normal
+            } else {
+                IngredientEtat::Coupe
+            };
+            assert_eq!(decoupe(&game).ingredient.etat, etat);
+        }
+
+        game.pickup(0).unwrap();
+        let coupe = Ingredient {
+            etat: IngredientEtat::Coupe,
+            ..pain
+        };
+        assert_eq!(
+            game.players[0].get_object_held(),
+            Some(Objet::Ingredient(coupe))
+        );
+        assert_eq!(game.map[0][2], Case::COUPER(None));
+    }
+
+    #[test]
+    fn l_assiette_servie_revient_sale_puis_se_lave() {
+        let mut game = partie(config(TARTINE), Clock::manual(), 0);
+        let config = game.get_config().clone();
+        let tartine = assiette(&[IngredientType::Pain, IngredientType::Salade]);
+
+        placer(&mut game, (4, 1), North, Some(Objet::Assiette(tartine)));
+        game.deposit(0).unwrap();
+        attendre(&mut game, config.delai_retour_assiette - PAS);
+        assert_eq!(game.map[0][5], Case::RETOUR(0));
+        attendre(&mut game, PAS);
+        assert_eq!(game.map[0][5], Case::RETOUR(1));
+
+        placer(&mut game, (5, 1), North, None);
+        game.pickup(0).unwrap();
+        assert_eq!(game.map[0][5], Case::RETOUR(0));
+        let sale = Some(Objet::Assiette(Assiette::sale()));
+        assert_eq!(game.players[0].get_object_held(), sale);
+
+        placer(&mut game, (5, 1), East, sale);
+        game.deposit(0).unwrap();
+        for coups in 1..=config.coups_lavage {
+            game.pickup(0).unwrap();
+            assert_eq!(game.players[0].get_object_held(), None);
+            assert_eq!(game.map[1][6], Case::EVIER(Some(Lavage { coups })));
+        }
+        game.pickup(0).unwrap();
+        assert_eq!(game.map[1][6], Case::EVIER(None));
+        assert_eq!(
+            game.players[0].get_object_held(),
+            Some(Objet::Assiette(Assiette::default()))
+        );
+    }
+
+    #[test]
+    fn la_passe_refuse_les_plats_faux_et_livre_le_bon() {
+        use IngredientType::{Pain, Salade, Viande};
+
+        let mut game = partie(
+            config("[Steak]\ningredients = pain | viande | pain\ndelai = [60, 60]"),
+            Clock::manual(),
+            0,
+        );
+        let commandes = game.get_recettes().len();
+        // un plat refusé reste en main
+        let mut servir = |types: &[IngredientType]| {
+            let plat = Some(Objet::Assiette(assiette(types)));
+            placer(&mut game, (4, 1), North, plat);
+            let servi = game.deposit(0);
+            assert_eq!(game.players[0].get_object_held().is_some(), servi.is_err());
+            servi
+        };
+        assert_eq!(servir(&[Pain, Viande]), Err(DepositError::PlatIncomplet));
+        assert_eq!(servir(&[Salade]), Err(DepositError::PlatNonCommande));
+        assert_eq!(
+            servir(&[Pain, Pain, Viande]),
+            Err(DepositError::PlatMalEmpile)
+        );
+        assert_eq!(servir(&[Pain, Viande, Pain]), Ok(()));
+
+        assert_eq!(game.get_recettes().len(), commandes - 1);
+        assert_eq!(game.get_recettes_livrees(), 1);
+    }
+
+    #[test]
+    fn la_poubelle_coute_par_ingredient_et_vide_l_assiette() {
+        let mut game = partie(
+            GameConfig {
+                penalite_poubelle: 3,
+                ..config(TARTINE)
+            },
+            Clock::manual(),
+            0,
+        );
+        let salade = Objet::Ingredient(Ingredient::new(IngredientType::Salade));
+        let vide = Some(Objet::Assiette(Assiette::default()));
+
+        placer(&mut game, (3, 1), South, Some(salade));
+        game.deposit(0).unwrap();
+        assert_eq!(game.players[0].get_object_held(), None);
+        assert_eq!(game.get_detail_score().poubelle, 3);
+
+        // le chef garde l'assiette, vidée
+        let tartine = assiette(&[IngredientType::Pain, IngredientType::Salade]);
+        placer(&mut game, (3, 1), South, Some(Objet::Assiette(tartine)));
+        game.deposit(0).unwrap();
+        assert_eq!(game.players[0].get_object_held(), vide);
+        assert_eq!(game.get_detail_score().poubelle, 9);
+        assert_eq!(game.get_score(), -9);
+
+        assert_eq!(game.deposit(0), Err(DepositError::NothingToDiscard));
+        assert_eq!(game.players[0].get_object_held(), vide);
+        assert_eq!(game.get_detail_score().poubelle, 9);
+    }
+
+    #[test]
+    fn retirer_prend_l_ingredient_choisi_dans_la_pile() {
+        use IngredientType::{Pain, Salade, Tomate, Viande};
+
+        let mut game = partie(config(TARTINE), Clock::manual(), 0);
+        game.map[0][1] = Case::ASSIETTE(Some(assiette(&[Pain, Viande, Salade])));
+
+        placer(&mut game, (1, 1), North, None);
+        game.retirer(0, pret(Viande)).unwrap();
+        assert_eq!(
+            game.players[0].get_object_held(),
+            Some(Objet::Ingredient(pret(Viande)))
+        );
+        assert_eq!(
+            game.map[0][1],
+            Case::ASSIETTE(Some(assiette(&[Pain, Salade])))
+        );
+        assert_eq!(game.retirer(0, pret(Pain)), Err(PickupError::HandsFull));
+
+        placer(&mut game, (1, 1), North, None);
+        assert_eq!(
+            game.retirer(0, pret(Tomate)),
+            Err(PickupError::IngredientAbsent)
+        );
+        game.map[0][1] = Case::ASSIETTE(None);
+        assert_eq!(game.retirer(0, pret(Pain)), Err(PickupError::AssietteEmpty));
+
+        placer(&mut game, (2, 1), North, None);
+        assert_eq!(
+            game.retirer(0, pret(Pain)),
+            Err(PickupError::NoTarget(((2, 0), Case::COUPER(None))))
+        );
+        assert_eq!(game.players[0].get_object_held(), None);
+    }
+}
@@ -0,0 +1,343 @@
+use std::{fs, io, ops::RangeInclusive, path::Path, str::FromStr, time::Duration};
+
+use rand::{Rng, seq::IndexedRandom};
+
+use crate::{
+    config::parse_range,
+    objets::{Assiette, Ingredient, IngredientEtat, IngredientType},
+};
+
+const LIVRE_PAR_DEFAUT: &str = include_str!("../recettes/default.txt");
+
+#[derive(Debug)]
+pub enum LivreError {
+    Io(io::Error),
+    Syntax { line: usize },
+    UnknownKey { line: usize, key: String },
+    InvalidValue { line: usize, key: String },
+    SansIngredients(String),
+    // le tirage pondéré additionne les poids en u32
+    PoidsTropGrands,
+    Vide,
+}
+
+impl From<io::Error> for LivreError {
+    fn from(error: io::Error) -> Self {
+        LivreError::Io(error)
+    }
+}
+
+impl std::fmt::Display for LivreError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            LivreError::Io(error) => write!(f, "lecture du livre de recettes impossible : {error}"),
+            LivreError::Syntax { line } => {
+                write!(f, "ligne {line} : `[Nom]` ou `cle = valeur` attendu")
+            }
+            LivreError::UnknownKey { line, key } => write!(f, "ligne {line} : clé inconnue {key}"),
+            LivreError::InvalidValue { line, key } => {
+                write!(f, "ligne {line} : valeur invalide pour {key}")
+            }
+            LivreError::SansIngredients(nom) => write!(f, "la recette {nom} n'a aucun ingrédient"),
+            LivreError::PoidsTropGrands => {
+                write!(f, "la somme des poids dépasse {}", u32::MAX)
+            }
+            LivreError::Vide => write!(f, "le livre ne contient aucune recette"),
+        }
+    }
+}
+
+impl std::error::Error for LivreError {}
+
+// Une recette du livre, les commandes sont tirées parmi elles
+#[derive(Debug, PartialEq, Clone)]
+pub struct ModeleRecette {
+    pub nom: String,
+    // couches à empiler du bas vers le haut, une seule si l'ordre est libre
+    pub couches: Vec<Vec<Ingredient>>,
+    // sans valeur, les points et le délai suivent la configuration
+    pub points: Option<i32>,
+    pub delai: Option<RangeInclusive<Duration>>,
+    // chance relative d'être commandée
+    pub poids: u32,
+}
+
+impl ModeleRecette {
+    fn new(nom: String) -> Self {
+        Self {
+            nom,
+            couches: Vec::new(),
+            points: None,
+            delai: None,
+            poids: 1,
+        }
+    }
+
+    pub fn ingredients(&self) -> impl Iterator<Item = Ingredient> + '_ {
+        self.couches.iter().flatten().copied()
+    }
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct LivreRecettes {
+    modeles: Vec<ModeleRecette>,
+}
+
+impl Default for LivreRecettes {
+    fn default() -> Self {
+        Self::from_str(LIVRE_PAR_DEFAUT).expect("Le livre de recettes par défaut est invalide")
+    }
+}
+
+fn parse_type(text: &str) -> Option<IngredientType> {
+    IngredientType::iter()
+        .into_iter()
+        .find(|type_ingredient| type_ingredient.to_string().eq_ignore_ascii_case(text))
+}
+
+// `viande` ou `viande:cuit` : sans état, l'ingrédient est demandé prêt
+fn parse_ingredient(text: &str) -> Option<Ingredient> {
+    let (type_ingredient, etat) = match text.split_once(':') {
+        Some((type_ingredient, etat)) => (type_ingredient.trim(), Some(etat.trim())),
+        None => (text, None),
+    };
+    let type_ingredient = parse_type(type_ingredient)?;
+    let etat = match etat {
+        None => type_ingredient.etat_pret(),
+        Some("normal" | "cru") => IngredientEtat::Normal,
+        Some("coupe" | "coupé") => IngredientEtat::Coupe,
+        Some("cuit") => IngredientEtat::Cuit,
+        Some(_) => return None,
+    };
+    // un ingrédient ne peut être que brut ou dans son état prêt
+    (etat == IngredientEtat::Normal || etat == type_ingredient.etat_pret()).then_some(Ingredient {
+        type_ingredient,
+        etat,
+    })
+}
+
+impl FromStr for LivreRecettes {
+    type Err = LivreError;
+
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        let mut modeles: Vec<ModeleRecette> = Vec::new();
+
+        for (i, line) in text.lines().enumerate() {
+            let line_number = i + 1;
+            let line = line.split('#').next().unwrap_or_default().trim();
+            if line.is_empty() {
+                continue;
+            }
+
+            if let Some(nom) = line.strip_prefix('[').and_then(|l| l.strip_suffix(']')) {
+                let nom = nom.trim();
+                if nom.is_empty() {
+                    return Err(LivreError::Syntax { line: line_number });
+                }
+                modeles.push(ModeleRecette::new(nom.to_string()));
+                continue;
+            }
+
+            let (key, value) = line
+                .split_once('=')
+                .ok_or(LivreError::Syntax { line: line_number })?;
+            let (key, value) = (key.trim(), value.trim());
+            let invalid = || LivreError::InvalidValue {
+                line: line_number,
+                key: key.to_string(),
+            };
+            // une clé avant la première recette n'appartient à rien
+            let modele = modeles
+                .last_mut()
+                .ok_or(LivreError::Syntax { line: line_number })?;
+
+            match key {
+                // `pain | viande, salade | pain` : des couches séparées par `|`
+                "ingredients" => {
+                    modele.couches = value
+                        .split('|')
+                        .map(|couche| {
+                            couche
+                                .split(',')
+                                .map(|ingredient| {
+                                    parse_ingredient(ingredient.trim().to_lowercase().as_str())
+                                })
+                                .collect::<Option<Vec<_>>>()
+                        })
+                        .collect::<Option<Vec<_>>>()
+                        // aucune assiette ne pourrait contenir la recette
+                        .filter(|couches| couches.iter().flatten().count() <= Assiette::CAPACITE)
+                        .ok_or_else(invalid)?
+                }
+                "points" => modele.points = Some(value.parse().map_err(|_| invalid())?),
+                "delai" => modele.delai = Some(parse_range(value).ok_or_else(invalid)?),
+                "poids" => modele.poids = value.parse().map_err(|_| invalid())?,
+                _ => {
+                    return Err(LivreError::UnknownKey {
+                        line: line_number,
+                        key: key.to_string(),
+                    });
+                }
+            }
+        }
+
+        if let Some(modele) = modeles.iter().find(|modele| modele.couches.is_empty()) {
+            return Err(LivreError::SansIngredients(modele.nom.clone()));
+        }
+        let total = modeles
+            .iter()
+            .try_fold(0u32, |total, modele| total.checked_add(modele.poids));
+        if total.is_none() {
+            return Err(LivreError::PoidsTropGrands);
+        }
+        if modeles.iter().all(|modele| modele.poids == 0) {
+            return Err(LivreError::Vide);
+        }
+
+        Ok(Self { modeles })
+    }
+}
+
+impl LivreRecettes {
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self, LivreError> {
+        let text = fs::read_to_string(path)?;
+        Self::from_str(&text)
+    }
+
+    pub fn get_modeles(&self) -> &Vec<ModeleRecette> {
+        &self.modeles
+    }
+
+    pub fn tirer(&self, rng: &mut impl Rng) -> &ModeleRecette {
+        self.modeles
+            .choose_weighted(rng, |modele| modele.poids)
+            .expect("Le livre contient au moins une recette de poids non nul")
+    }
+
+    // tous les types d'ingrédients que le livre peut demander
+    pub fn types_requis(&self) -> Vec<IngredientType> {
+        let mut types = self
+            .modeles
+            .iter()
+            .filter(|modele| modele.poids > 0)
+            .flat_map(|modele| modele.ingredients().map(|i| i.type_ingredient))
+            .collect::<Vec<_>>();
+        types.sort();
+        types.dedup();
+        types
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ingredient(type_ingredient: IngredientType, etat: IngredientEtat) -> Ingredient {
+        Ingredient {
+            type_ingredient,
+            etat,
+        }
+    }
+
+    #[test]
+    fn les_livres_fournis_se_lisent() {
+        LivreRecettes::default();
+        LivreRecettes::from_str(include_str!("../recettes/debutant.txt")).unwrap();
+    }
+
+    #[test]
+    fn les_couches_les_repetitions_et_les_etats_sont_lus() {
+        let livre = LivreRecettes::from_str(
+            "[Burger]\ningredients = Pain | viande:cru, salade, salade | pain # haut\npoids = 0\n\n[Tartine]\ningredients = pain, tomate\npoints = 8\ndelai = [20, 35]",
+        )
+        .unwrap();
+        let [burger, tartine] = livre.get_modeles().as_slice() else {
+            panic!("deux recettes attendues");
+        };
+
+        let pain = ingredient(IngredientType::Pain, IngredientEtat::Coupe);
+        let salade = ingredient(IngredientType::Salade, IngredientEtat::Coupe);
+        let viande = ingredient(IngredientType::Viande, IngredientEtat::Normal);
+        assert_eq!(
+            burger.couches,
+            vec![vec![pain], vec![viande, salade, salade], vec![pain]]
+        );
+        assert_eq!(burger.poids, 0);
+        assert_eq!(burger.points, None);
+
+        assert_eq!(tartine.couches.len(), 1);
+        assert_eq!(tartine.poids, 1);
+        assert_eq!(tartine.points, Some(8));
+        assert_eq!(
+            tartine.delai,
+            Some(Duration::from_secs(20)..=Duration::from_secs(35))
+        );
+        assert_eq!(
+            livre.types_requis(),
+            vec![IngredientType::Pain, IngredientType::Tomate]
+        );
+    }
+
+    #[test]
+    fn une_recette_plus_grande_qu_une_assiette_est_refusee() {
+        let pleine = ["pain"; Assiette::CAPACITE].join(", ");
+        assert!(LivreRecettes::from_str(&format!("[Pleine]\ningredients = {pleine}")).is_ok());
+        assert!(matches!(
+            LivreRecettes::from_str(&format!("[Trop]\n\ningredients = {pleine} | salade")),
+            Err(LivreError::InvalidValue { line: 3, key }) if key == "ingredients"
+        ));
+    }
+
+    #[test]
+    fn les_erreurs_donnent_leur_ligne() {
+        assert!(matches!(
+            LivreRecettes::from_str("ingredients = pain"),
+            Err(LivreError::Syntax { line: 1 })
+        ));
+        assert!(matches!(
+            LivreRecettes::from_str("[ ]\ningredients = pain"),
+            Err(LivreError::Syntax { line: 1 })
+        ));
+        assert!(matches!(
+            LivreRecettes::from_str("[Tartine]\ningredients pain"),
+            Err(LivreError::Syntax { line: 2 })
+        ));
+        assert!(matches!(
+            LivreRecettes::from_str("[Tartine]\ningredients = pain, fromage"),
+            Err(LivreError::InvalidValue { line: 2, .. })
+        ));
+        // le pain ne se cuit pas
+        assert!(matches!(
+            LivreRecettes::from_str("[Tartine]\ningredients = pain:cuit"),
+            Err(LivreError::InvalidValue { line: 2, .. })
+        ));
+        assert!(matches!(
+            LivreRecettes::from_str("[Tartine]\ningredients = pain\nsauce = oui"),
+            Err(LivreError::UnknownKey { line: 3, key }) if key == "sauce"
+        ));
+    }
+
+    #[test]
+    fn un_livre_doit_pouvoir_commander_quelque_chose() {
+        assert!(matches!(
+            LivreRecettes::from_str("[Tartine]\ningredients = pain\n[Vide]\npoids = 2"),
+            Err(LivreError::SansIngredients(nom)) if nom == "Vide"
+        ));
+        assert!(matches!(
+            LivreRecettes::from_str("[Tartine]\ningredients = pain\npoids = 0"),
+            Err(LivreError::Vide)
+        ));
+        assert!(matches!(LivreRecettes::from_str(""), Err(LivreError::Vide)));
+    }
+
+    #[test]
+    fn des_poids_dont_la_somme_deborde_sont_refuses() {
+        let livre = |poids: u32| {
+            LivreRecettes::from_str(&format!(
+                "[Tartine]\ningredients = pain\npoids = {poids}\n[Verdure]\ningredients = salade\npoids = {poids}"
+            ))
+        };
+        assert!(livre(u32::MAX / 2).is_ok());
+        assert!(matches!(livre(u32::MAX), Err(LivreError::PoidsTropGrands)));
+    }
+}
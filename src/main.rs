@@ -1,22 +1,38 @@
 use color_eyre::Result;
-use hai716i_poasma::app::App;
+use hai716i_poasma::{
+    app::App,
+    campagne::EtatCampagne,
+    cli::{Cli, CliError, Mode},
+    simulation::simulate,
+    strategy::strategie_par_defaut,
+};
 
 fn main() -> Result<()> {
     color_eyre::install()?;
+    let cli = match Cli::parse(std::env::args().skip(1)) {
+        Err(CliError::Help) => {
+            println!("{}", CliError::Help);
+            return Ok(());
+        }
+        cli => cli?,
+    };
+
+    if cli.mode == Mode::Headless {
+        // en campagne, joue le niveau où le joueur en est, sans toucher à sa progression
+        let game = match EtatCampagne::from_cli(&cli)? {
+            Some(campagne) => campagne.build_game(&cli)?,
+            None => cli.build_game()?,
+        };
+        let game = game.with_seed(cli.seed.unwrap_or_else(rand::random));
+        let mut strategy = strategie_par_defaut(game.get_players().len());
+        let result = simulate(game, strategy.as_mut());
+        println!("{result}\n{}", result.detail);
+        return Ok(());
+    }
+
+    let mut app = App::from_cli(&cli)?;
     let mut terminal = ratatui::init();
-    let mut app = App::new();
-    let result = app.run(&mut terminal, true);
+    let result = app.run(&mut terminal);
     ratatui::restore();
     Ok(result?)
 }
-
-// fn main() {
-//     let mut game = HAI716I_POASMA::game::Game::new(3000000);
-//     loop {
-//         game.robot();
-//         game.update();
-//         // print!("\x1B[2J\x1B[1;1H");
-//         // println!("MAP:\n{}", game);
-//         // std::thread::sleep(std::time::Duration::from_millis(200));
-//     }
-// }
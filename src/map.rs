@@ -0,0 +1,425 @@
+use std::io;
+
+use crate::{
+    objets::{Assiette, Case, Ingredient, IngredientEtat, IngredientType},
+    pathfinding::DistanceMap,
+};
+
+pub const SPAWN_CHAR: char = '·';
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct MapLayout {
+    pub map: Vec<Vec<Case>>,
+    // dans l'ordre de lecture : le premier est celui du chef 0
+    pub spawns: Vec<(usize, usize)>,
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub enum MapProblem {
+    Empty,
+    RaggedRow {
+        line: usize,
+        width: usize,
+        expected: usize,
+    },
+    MissingBorder((usize, usize)),
+    NotEnoughSpawns {
+        needed: usize,
+        found: usize,
+    },
+    SpawnBlocked((usize, usize)),
+    MissingAssiette,
+    MissingCouper,
+    MissingPoele,
+    MissingPasse,
+    MissingRetour,
+    MissingEvier,
+    MissingIngredient(IngredientType),
+    Unreachable(((usize, usize), Case)),
+}
+
+impl std::fmt::Display for MapProblem {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            MapProblem::Empty => write!(f, "la carte est vide"),
+            MapProblem::RaggedRow {
+                line,
+                width,
+                expected,
+            } => write!(f, "ligne {line} : {width} cases au lieu de {expected}"),
+            MapProblem::MissingBorder(pos) => {
+                write!(f, "case vide en {pos:?} sur le bord de la carte")
+            }
+            MapProblem::NotEnoughSpawns { needed, found } => {
+                write!(f, "{needed} chefs pour {found} points d'apparition")
+            }
+            MapProblem::SpawnBlocked(pos) => {
+                write!(
+                    f,
+                    "un chef apparaît en {pos:?} qui n'est pas une case vide libre"
+                )
+            }
+            MapProblem::MissingAssiette => write!(f, "aucune assiette"),
+            MapProblem::MissingCouper => write!(f, "aucune planche à découper"),
+            MapProblem::MissingPoele => write!(f, "aucune poêle"),
+            MapProblem::MissingPasse => write!(f, "aucune passe pour servir"),
+            MapProblem::MissingRetour => write!(f, "aucun retour des assiettes sales"),
+            MapProblem::MissingEvier => write!(f, "aucun évier"),
+            MapProblem::MissingIngredient(ingredient) => {
+                write!(f, "aucune caisse de {ingredient}")
+            }
+            MapProblem::Unreachable((pos, case)) => {
+                write!(f, "{case:?} en {pos:?} inaccessible depuis les chefs")
+            }
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum MapError {
+    Io(io::Error),
+    UnknownChar {
+        line: usize,
+        column: usize,
+        char: char,
+    },
+    NoSpawn,
+    Invalid(Vec<MapProblem>),
+}
+
+impl From<io::Error> for MapError {
+    fn from(error: io::Error) -> Self {
+        MapError::Io(error)
+    }
+}
+
+impl std::fmt::Display for MapError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            MapError::Io(error) => write!(f, "lecture de la carte impossible : {error}"),
+            MapError::UnknownChar { line, column, char } => {
+                write!(
+                    f,
+                    "ligne {line}, colonne {column} : caractère inconnu '{char}'"
+                )
+            }
+            MapError::NoSpawn => write!(f, "aucun point d'apparition ('{SPAWN_CHAR}')"),
+            MapError::Invalid(problems) => {
+                let problems = problems
+                    .iter()
+                    .map(MapProblem::to_string)
+                    .collect::<Vec<_>>()
+                    .join(", ");
+                write!(f, "carte injouable : {problems}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for MapError {}
+
+pub fn case_from_char(c: char) -> Option<Case> {
+    let case = match c {
+        ' ' | SPAWN_CHAR => Case::Vide,
+        '#' => Case::Table(None),
+        'C' => Case::COUPER(None),
+        'A' => Case::ASSIETTE(Some(Assiette::default())),
+        'F' => Case::POELE(None),
+        'H' => Case::PASSE,
+        'R' => Case::RETOUR(0),
+        'E' => Case::EVIER(None),
+        'X' => Case::POUBELLE,
+        _ => {
+            let type_ingredient = IngredientType::iter()
+                .into_iter()
+                .find(|ingr| ingr.char() == c || ingr.upper_char() == c)?;
+            if c.is_uppercase() {
+                Case::Ingredient(type_ingredient)
+            } else {
+                Case::Table(Some(Ingredient::new(type_ingredient)))
+            }
+        }
+    };
+    Some(case)
+}
+
+// Une ligne du texte = une ligne de la carte, un caractère = une case
+pub fn parse_map(text: &str) -> Result<MapLayout, MapError> {
+    let mut map = Vec::new();
+    let mut spawns = Vec::new();
+
+    for (y, line) in text.lines().enumerate() {
+        let mut row = Vec::new();
+        for (x, c) in line.chars().enumerate() {
+            let case = case_from_char(c).ok_or(MapError::UnknownChar {
+                line: y + 1,
+                column: x + 1,
+                char: c,
+            })?;
+            if c == SPAWN_CHAR {
+                spawns.push((x, y));
+            }
+            row.push(case);
+        }
+        map.push(row);
+    }
+
+    if spawns.is_empty() {
+        return Err(MapError::NoSpawn);
+    }
+    Ok(MapLayout { map, spawns })
+}
+
+// Vérifie qu'une partie peut être jouée sur cette carte
+pub fn validate_map(
+    map: &[Vec<Case>],
+    spawns: &[(usize, usize)],
+    types_requis: &[IngredientType],
+) -> Result<(), Vec<MapProblem>> {
+    let Some(expected) = map.first().map(Vec::len).filter(|&width| width > 0) else {
+        return Err(vec![MapProblem::Empty]);
+    };
+
+    let mut problems: Vec<MapProblem> = map
+        .iter()
+        .enumerate()
+        .filter(|(_, row)| row.len() != expected)
+        .map(|(y, row)| MapProblem::RaggedRow {
+            line: y + 1,
+            width: row.len(),
+            expected,
+        })
+        .collect();
+    if !problems.is_empty() {
+        return Err(problems);
+    }
+
+    let (width, height) = (expected, map.len());
+    for (y, row) in map.iter().enumerate() {
+        for (x, case) in row.iter().enumerate() {
+            let on_border = x == 0 || y == 0 || x == width - 1 || y == height - 1;
+            if on_border && *case == Case::Vide {
+                problems.push(MapProblem::MissingBorder((x, y)));
+            }
+        }
+    }
+
+    for (i, &spawn) in spawns.iter().enumerate() {
+        let is_vide = map.get(spawn.1).and_then(|row| row.get(spawn.0)) == Some(&Case::Vide);
+        if !is_vide || spawns[..i].contains(&spawn) {
+            problems.push(MapProblem::SpawnBlocked(spawn));
+        }
+    }
+    if problems
+        .iter()
+        .any(|problem| matches!(problem, MapProblem::SpawnBlocked(_)))
+    {
+        return Err(problems);
+    }
+
+    // cases vides atteignables depuis au moins un point d'apparition
+    let distances = spawns
+        .iter()
+        .map(|&spawn| DistanceMap::new(map, spawn))
+        .collect::<Vec<_>>();
+    let is_accessible = |pos: (usize, usize)| {
+        distances
+            .iter()
+            .any(|distances| distances.route_to(map, pos).is_some())
+    };
+
+    let mut stations = vec![
+        (
+            Case::ASSIETTE(Some(Assiette::default())),
+            MapProblem::MissingAssiette,
+        ),
+        (Case::COUPER(None), MapProblem::MissingCouper),
+        (Case::PASSE, MapProblem::MissingPasse),
+        (Case::RETOUR(0), MapProblem::MissingRetour),
+        (Case::EVIER(None), MapProblem::MissingEvier),
+    ];
+    // sans ingrédient à servir cuit, la cuisine peut se passer de poêle
+    if types_requis
+        .iter()
+        .any(|type_ingredient| type_ingredient.etat_pret() == IngredientEtat::Cuit)
+    {
+        stations.push((Case::POELE(None), MapProblem::MissingPoele));
+    }
+    for &type_ingredient in types_requis {
+        stations.push((
+            Case::Ingredient(type_ingredient),
+            MapProblem::MissingIngredient(type_ingredient),
+        ));
+    }
+
+    for (station, missing) in stations {
+        let positions = map
+            .iter()
+            .enumerate()
+            .flat_map(|(y, row)| {
+                row.iter()
+                    .enumerate()
+                    .filter(move |(_, case)| **case == station)
+                    .map(move |(x, _)| (x, y))
+            })
+            .collect::<Vec<_>>();
+        match positions.first() {
+            None => problems.push(missing),
+            Some(&pos) if !positions.iter().any(|&pos| is_accessible(pos)) => {
+                problems.push(MapProblem::Unreachable((pos, station)))
+            }
+            _ => (),
+        }
+    }
+
+    if problems.is_empty() {
+        Ok(())
+    } else {
+        Err(problems)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn la_carte_par_defaut_se_lit() {
+        let layout = parse_map(include_str!("../maps/default.txt")).unwrap();
+        assert_eq!(layout.map.len(), 10);
+        assert_eq!(layout.spawns, vec![(1, 1), (13, 1)]);
+        assert_eq!(layout.map[0][10], Case::POELE(None));
+        assert_eq!(layout.map[1][0], Case::Ingredient(IngredientType::Pain));
+        assert_eq!(layout.map[1][1], Case::Vide);
+    }
+
+    #[test]
+    fn une_minuscule_pose_l_ingredient_sur_une_table() {
+        let layout = parse_map("#p#\n#·#\n###").unwrap();
+        assert_eq!(
+            layout.map[0][1],
+            Case::Table(Some(Ingredient::new(IngredientType::Pain)))
+        );
+    }
+
+    #[test]
+    fn un_caractere_inconnu_donne_sa_ligne_et_sa_colonne() {
+        let error = parse_map("###\n#·?\n###").unwrap_err();
+        assert!(matches!(
+            error,
+            MapError::UnknownChar {
+                line: 2,
+                column: 3,
+                char: '?'
+            }
+        ));
+    }
+
+    #[test]
+    fn une_carte_sans_point_d_apparition_est_refusee() {
+        assert!(matches!(parse_map("###\n# #\n###"), Err(MapError::NoSpawn)));
+    }
+
+    // une cuisine minimale : une caisse de pain et une de salade, rien à cuire
+    const CUISINE: &str = "#ACHRE#\nP·    #\n#S##F##";
+
+    fn valider(text: &str, types_requis: &[IngredientType]) -> Result<(), Vec<MapProblem>> {
+        let layout = parse_map(text).unwrap();
+        validate_map(&layout.map, &layout.spawns, types_requis)
+    }
+
+    #[test]
+    fn une_cuisine_complete_est_valide() {
+        let types = [IngredientType::Pain, IngredientType::Salade];
+        assert_eq!(valider(CUISINE, &types), Ok(()));
+        assert_eq!(
+            valider(include_str!("../maps/default.txt"), &IngredientType::iter()),
+            Ok(())
+        );
+    }
+
+    #[test]
+    fn la_poele_n_est_demandee_que_pour_cuire() {
+        let sans_poele = CUISINE.replace('F', "#");
+        assert_eq!(
+            valider(&sans_poele, &[IngredientType::Pain, IngredientType::Salade]),
+            Ok(())
+        );
+        assert_eq!(
+            valider(
+                &sans_poele,
+                &[
+                    IngredientType::Pain,
+                    IngredientType::Salade,
+                    IngredientType::Viande
+                ]
+            ),
+            Err(vec![
+                MapProblem::MissingPoele,
+                MapProblem::MissingIngredient(IngredientType::Viande)
+            ])
+        );
+    }
+
+    #[test]
+    fn une_ligne_trop_courte_est_signalee() {
+        assert_eq!(
+            valider("#ACHRE#\nP·   #\n#S##F##", &[]),
+            Err(vec![MapProblem::RaggedRow {
+                line: 2,
+                width: 6,
+                expected: 7
+            }])
+        );
+        assert_eq!(validate_map(&[], &[], &[]), Err(vec![MapProblem::Empty]));
+    }
+
+    #[test]
+    fn un_trou_dans_le_bord_est_signale() {
+        assert_eq!(
+            valider("#ACHRE#\nP·     \n#S##F##", &[IngredientType::Pain]),
+            Err(vec![MapProblem::MissingBorder((6, 1))])
+        );
+    }
+
+    #[test]
+    fn un_point_d_apparition_bloque_ou_double_est_signale() {
+        let layout = parse_map(CUISINE).unwrap();
+        assert_eq!(
+            validate_map(&layout.map, &[(1, 1), (0, 1)], &[]),
+            Err(vec![MapProblem::SpawnBlocked((0, 1))])
+        );
+        assert_eq!(
+            validate_map(&layout.map, &[(1, 1), (2, 1), (1, 1)], &[]),
+            Err(vec![MapProblem::SpawnBlocked((1, 1))])
+        );
+    }
+
+    #[test]
+    fn les_postes_manquants_sont_signales() {
+        assert_eq!(
+            valider("#######\nP·    #\n#S##F##", &[IngredientType::Pain]),
+            Err(vec![
+                MapProblem::MissingAssiette,
+                MapProblem::MissingCouper,
+                MapProblem::MissingPasse,
+                MapProblem::MissingRetour,
+                MapProblem::MissingEvier,
+            ])
+        );
+    }
+
+    #[test]
+    fn une_caisse_emmuree_est_inaccessible() {
+        assert_eq!(
+            valider(
+                "#ACHRE#\nP·    #\n#S#####\n####T##",
+                &[IngredientType::Pain, IngredientType::Tomate]
+            ),
+            Err(vec![MapProblem::Unreachable((
+                (4, 3),
+                Case::Ingredient(IngredientType::Tomate)
+            ))])
+        );
+    }
+}
@@ -1,11 +1,10 @@
-use std::collections::HashSet;
+use std::collections::BTreeMap;
 use std::time::Instant;
 use std::{fmt::Display, time::Duration};
 
 use rand::Rng;
-use rand::seq::SliceRandom;
 
-use crate::recette_deadline_range;
+use crate::config::GameConfig;
 
 #[derive(Debug, PartialEq, Eq, Clone, Copy)]
 pub enum Direction {
@@ -16,6 +15,20 @@ pub enum Direction {
 }
 
 impl Direction {
+    // direction d'une case vers une case voisine
+    pub fn between(from: (usize, usize), to: (usize, usize)) -> Option<Self> {
+        match (
+            to.0 as isize - from.0 as isize,
+            to.1 as isize - from.1 as isize,
+        ) {
+            (0, -1) => Some(Direction::North),
+            (-1, 0) => Some(Direction::West),
+            (0, 1) => Some(Direction::South),
+            (1, 0) => Some(Direction::East),
+            _ => None,
+        }
+    }
+
     pub fn emoji(&self) -> &'static str {
         match self {
             Direction::North => "⬆️",
@@ -32,6 +45,7 @@ pub enum IngredientType {
     Salade,
     Tomate,
     Oignon,
+    Viande,
 }
 
 impl IngredientType {
@@ -41,6 +55,7 @@ impl IngredientType {
             IngredientType::Salade => 's',
             IngredientType::Tomate => 't',
             IngredientType::Oignon => 'o',
+            IngredientType::Viande => 'v',
         }
     }
 
@@ -54,6 +69,15 @@ impl IngredientType {
             IngredientType::Salade => "🥬",
             IngredientType::Tomate => "🍅",
             IngredientType::Oignon => "🧅",
+            IngredientType::Viande => "🥩",
+        }
+    }
+
+    // l'état dans lequel une recette demande l'ingrédient
+    pub fn etat_pret(&self) -> IngredientEtat {
+        match self {
+            IngredientType::Viande => IngredientEtat::Cuit,
+            _ => IngredientEtat::Coupe,
         }
     }
 
@@ -63,6 +87,7 @@ impl IngredientType {
             IngredientType::Salade,
             IngredientType::Tomate,
             IngredientType::Oignon,
+            IngredientType::Viande,
         ]
     }
 }
@@ -74,6 +99,7 @@ impl Display for IngredientType {
             IngredientType::Salade => "Salade",
             IngredientType::Tomate => "Tomate",
             IngredientType::Oignon => "Oignon",
+            IngredientType::Viande => "Viande",
         };
         write!(f, "{str}")
     }
@@ -83,6 +109,9 @@ impl Display for IngredientType {
 pub enum IngredientEtat {
     Normal,
     Coupe,
+    Cuit,
+    // trop cuit, plus aucune recette n'en veut
+    Brule,
 }
 
 impl Display for IngredientEtat {
@@ -90,6 +119,8 @@ impl Display for IngredientEtat {
         let str = match self {
             IngredientEtat::Normal => "Normal",
             IngredientEtat::Coupe => "Coupé",
+            IngredientEtat::Cuit => "Cuit",
+            IngredientEtat::Brule => "Brûlé",
         };
         write!(f, "{str}")
     }
@@ -109,6 +140,14 @@ impl Ingredient {
         }
     }
 
+    // tel que le demandent les recettes
+    pub fn pret(type_ingredient: IngredientType) -> Self {
+        Self {
+            type_ingredient,
+            etat: type_ingredient.etat_pret(),
+        }
+    }
+
     pub fn emoji(&self) -> &'static str {
         match (self.type_ingredient, self.etat) {
             (IngredientType::Pain, IngredientEtat::Normal) => "🥖",
@@ -119,11 +158,37 @@ impl Ingredient {
             (IngredientType::Tomate, IngredientEtat::Coupe) => "🍅",
             (IngredientType::Oignon, IngredientEtat::Normal) => "🧅",
             (IngredientType::Oignon, IngredientEtat::Coupe) => "🧅",
+            (IngredientType::Viande, IngredientEtat::Cuit) => "🍖",
+            (_, IngredientEtat::Brule) => "🔥",
+            (type_ingredient, _) => type_ingredient.emoji(),
         }
     }
 
+    // seuls les ingrédients qui se coupent changent sur la planche
     pub fn couper(&mut self) {
-        self.etat = IngredientEtat::Coupe;
+        if self.etat == IngredientEtat::Normal
+            && self.type_ingredient.etat_pret() == IngredientEtat::Coupe
+        {
+            self.etat = IngredientEtat::Coupe;
+        }
+    }
+
+    pub fn se_coupe(&self) -> bool {
+        self.etat == IngredientEtat::Normal
+            && self.type_ingredient.etat_pret() == IngredientEtat::Coupe
+    }
+
+    pub fn se_cuit(&self) -> bool {
+        self.etat == IngredientEtat::Normal
+            && self.type_ingredient.etat_pret() == IngredientEtat::Cuit
+    }
+
+    // l'ingrédient peut-il encore être transformé en `voulu` ?
+    pub fn peut_devenir(&self, voulu: &Ingredient) -> bool {
+        self.type_ingredient == voulu.type_ingredient
+            && (self == voulu
+                || (self.etat == IngredientEtat::Normal
+                    && voulu.etat == voulu.type_ingredient.etat_pret()))
     }
 
     pub fn into_coupe(mut self) -> Self {
@@ -138,53 +203,387 @@ impl Display for Ingredient {
     }
 }
 
+// Ingrédients comptés avec leurs répétitions : deux tranches de tomate ne valent pas une
+#[derive(Debug, PartialEq, Eq, Clone, Default)]
+pub struct Contenu(BTreeMap<Ingredient, usize>);
+
+impl Contenu {
+    // nombre total d'ingrédients, répétitions comprises
+    pub fn len(&self) -> usize {
+        self.0.values().sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    pub fn count(&self, ingredient: &Ingredient) -> usize {
+        self.0.get(ingredient).copied().unwrap_or_default()
+    }
+
+    pub fn contains(&self, ingredient: &Ingredient) -> bool {
+        self.0.contains_key(ingredient)
+    }
+
+    pub fn ajouter(&mut self, ingredient: Ingredient) {
+        *self.0.entry(ingredient).or_default() += 1;
+    }
+
+    pub fn retirer(&mut self, ingredient: &Ingredient) -> bool {
+        let Some(nombre) = self.0.get_mut(ingredient) else {
+            return false;
+        };
+        *nombre -= 1;
+        if *nombre == 0 {
+            self.0.remove(ingredient);
+        }
+        true
+    }
+
+    // chaque ingrédient autant de fois qu'il est présent, dans l'ordre
+    pub fn iter(&self) -> impl Iterator<Item = Ingredient> + '_ {
+        self.0
+            .iter()
+            .flat_map(|(ingredient, &nombre)| std::iter::repeat_n(*ingredient, nombre))
+    }
+
+    // ce qu'on a en plus de `autre`
+    pub fn difference(&self, autre: &Contenu) -> Contenu {
+        Contenu(
+            self.0
+                .iter()
+                .filter_map(|(ingredient, &nombre)| {
+                    let reste = nombre.saturating_sub(autre.count(ingredient));
+                    (reste > 0).then_some((*ingredient, reste))
+                })
+                .collect(),
+        )
+    }
+
+    pub fn is_subset(&self, autre: &Contenu) -> bool {
+        self.difference(autre).is_empty()
+    }
+
+    // ingrédients à ajouter ou retirer pour passer de l'un à l'autre
+    pub fn ecart(&self, autre: &Contenu) -> usize {
+        self.difference(autre).len() + autre.difference(self).len()
+    }
+}
+
+impl FromIterator<Ingredient> for Contenu {
+    fn from_iter<T: IntoIterator<Item = Ingredient>>(iter: T) -> Self {
+        let mut contenu = Contenu::default();
+        for ingredient in iter {
+            contenu.ajouter(ingredient);
+        }
+        contenu
+    }
+}
+
+// Une assiette qu'on remplit, sert, puis lave avant de la réutiliser
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy, Default)]
+pub struct Assiette {
+    ingredients: [Option<Ingredient>; Assiette::CAPACITE],
+    pub sale: bool,
+}
+
+impl Assiette {
+    pub const CAPACITE: usize = 8;
+
+    pub fn sale() -> Self {
+        Self {
+            sale: true,
+            ..Self::default()
+        }
+    }
+
+    // dans l'ordre où ils ont été posés
+    pub fn get_ingredients(&self) -> Vec<Ingredient> {
+        self.ingredients.iter().flatten().copied().collect()
+    }
+
+    pub fn contenu(&self) -> Contenu {
+        self.ingredients.iter().flatten().copied().collect()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.ingredients.iter().all(Option::is_none)
+    }
+
+    // retire la première occurrence de l'ingrédient, ceux du dessus descendent d'un cran
+    pub fn retirer(&mut self, ingredient: &Ingredient) -> bool {
+        let Some(i) = self
+            .ingredients
+            .iter()
+            .position(|place| place.as_ref() == Some(ingredient))
+        else {
+            return false;
+        };
+        self.ingredients[i..].rotate_left(1);
+        self.ingredients[Self::CAPACITE - 1] = None;
+        true
+    }
+
+    pub fn vider(&mut self) {
+        self.ingredients = Default::default();
+    }
+
+    // false si l'assiette est pleine
+    pub fn ajouter(&mut self, ingredient: Ingredient) -> bool {
+        match self.ingredients.iter_mut().find(|place| place.is_none()) {
+            Some(place) => {
+                *place = Some(ingredient);
+                true
+            }
+            None => false,
+        }
+    }
+
+    // le dessus de l'assiette, ce qu'on voit en premier
+    pub fn emoji(&self) -> &'static str {
+        match self.ingredients.iter().flatten().last() {
+            Some(ingredient) => ingredient.emoji(),
+            None => "🍽️",
+        }
+    }
+}
+
+// Ce qu'un chef peut avoir en main
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
+pub enum Objet {
+    Ingredient(Ingredient),
+    Assiette(Assiette),
+}
+
+impl Objet {
+    pub fn ingredient(self) -> Option<Ingredient> {
+        match self {
+            Objet::Ingredient(ingredient) => Some(ingredient),
+            Objet::Assiette(_) => None,
+        }
+    }
+
+    pub fn assiette(self) -> Option<Assiette> {
+        match self {
+            Objet::Assiette(assiette) => Some(assiette),
+            Objet::Ingredient(_) => None,
+        }
+    }
+
+    pub fn emoji(&self) -> &'static str {
+        match self {
+            Objet::Ingredient(ingredient) => ingredient.emoji(),
+            Objet::Assiette(assiette) if assiette.sale => "🥣",
+            Objet::Assiette(assiette) => assiette.emoji(),
+        }
+    }
+}
+
+// Assiette sale dans l'évier : chaque coup d'éponge la rapproche d'être propre
+#[derive(Debug, PartialEq, Clone, Copy, Default)]
+pub struct Lavage {
+    pub coups: u32,
+}
+
+impl Lavage {
+    pub fn avancer(&mut self) {
+        self.coups += 1;
+    }
+
+    pub fn coups_restants(&self, config: &GameConfig) -> u32 {
+        config.coups_lavage.saturating_sub(self.coups)
+    }
+
+    pub fn progression(&self, config: &GameConfig) -> f32 {
+        (self.coups as f32 / config.coups_lavage.max(1) as f32).min(1.)
+    }
+
+    pub fn is_propre(&self, config: &GameConfig) -> bool {
+        self.coups >= config.coups_lavage
+    }
+}
+
+// Ingrédient posé sur une planche : chaque coup de couteau le rapproche d'être coupé
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct Decoupe {
+    pub ingredient: Ingredient,
+    pub coups: u32,
+}
+
+impl Decoupe {
+    pub fn new(ingredient: Ingredient) -> Self {
+        Self {
+            ingredient,
+            coups: 0,
+        }
+    }
+
+    pub fn avancer(&mut self, config: &GameConfig) {
+        self.coups += 1;
+        if self.coups >= config.coups_decoupe {
+            self.ingredient.couper();
+        }
+    }
+
+    pub fn coups_restants(&self, config: &GameConfig) -> u32 {
+        config.coups_decoupe.saturating_sub(self.coups)
+    }
+
+    pub fn progression(&self, config: &GameConfig) -> f32 {
+        (self.coups as f32 / config.coups_decoupe.max(1) as f32).min(1.)
+    }
+
+    pub fn is_pret(&self) -> bool {
+        self.ingredient.etat != IngredientEtat::Normal
+    }
+}
+
+// Ingrédient posé sur une poêle : il cuit, puis brûle si on le laisse trop longtemps
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct Cuisson {
+    pub ingredient: Ingredient,
+    pub debut: Instant,
+}
+
+impl Cuisson {
+    pub fn new(ingredient: Ingredient, debut: Instant) -> Self {
+        Self { ingredient, debut }
+    }
+
+    pub fn avancer(&mut self, now: Instant, config: &GameConfig) {
+        let temps = now.saturating_duration_since(self.debut);
+        if temps >= config.duree_cuisson + config.delai_brulure {
+            self.ingredient.etat = IngredientEtat::Brule;
+        } else if temps >= config.duree_cuisson {
+            self.ingredient.etat = IngredientEtat::Cuit;
+        }
+    }
+
+    // avancement de la cuisson, au-delà de 1 la viande commence à brûler
+    pub fn progression(&self, now: Instant, config: &GameConfig) -> f32 {
+        now.saturating_duration_since(self.debut).as_secs_f32() / config.duree_cuisson.as_secs_f32()
+    }
+
+    pub fn is_pret(&self) -> bool {
+        self.ingredient.etat != IngredientEtat::Normal
+    }
+}
+
 #[derive(Debug, PartialEq, Clone, Copy)]
 pub enum Case {
     Vide,
     Table(Option<Ingredient>),
     Ingredient(IngredientType),
-    COUPER,
-    ASSIETTE,
+    COUPER(Option<Decoupe>),
+    // emplacement où l'on dresse une assiette
+    ASSIETTE(Option<Assiette>),
+    POELE(Option<Cuisson>),
+    // passe où l'on sert les plats commandés
+    PASSE,
+    // retour des assiettes sales, empilées
+    RETOUR(usize),
+    EVIER(Option<Lavage>),
+    // détruit un ingrédient, ou vide une assiette
+    POUBELLE,
 }
 #[derive(Debug, PartialEq, Clone)]
 pub struct Recette {
-    pub ingredients: HashSet<Ingredient>,
+    pub nom: String,
+    pub ingredients: Contenu,
+    // une recette ordonnée s'empile couche par couche, l'ordre est libre dans une couche
+    pub couches: Vec<Vec<Ingredient>>,
+    pub points: i32,
     pub creation: Instant,
     pub duree: Duration,
     pub expiration: Instant,
 }
 
 impl Recette {
-    pub fn new(creation: Instant) -> Self {
-        let mut rng = rand::rng();
-        let mut ingredients = vec![Ingredient::new(IngredientType::Pain).into_coupe()];
-        let mut possibles = [
-            Ingredient::new(IngredientType::Salade).into_coupe(),
-            Ingredient::new(IngredientType::Tomate).into_coupe(),
-            Ingredient::new(IngredientType::Oignon).into_coupe(),
-        ];
-        let n = rng.random_range(1..=possibles.len());
-        possibles.shuffle(&mut rng);
-        for choice in possibles.into_iter().take(n) {
-            ingredients.push(choice);
-        }
-
-        let duree = rng.random_range(recette_deadline_range(ingredients.len()));
+    // tirée au hasard dans le livre de recettes de la configuration
+    pub fn new(creation: Instant, rng: &mut impl Rng, config: &GameConfig) -> Self {
+        let modele = config.livre.tirer(rng);
+        let nombre = modele.ingredients().count();
+        let delai = modele
+            .delai
+            .clone()
+            .unwrap_or_else(|| config.delai_recette(nombre));
+        let duree = rng.random_range(delai);
         let expiration = creation + duree;
 
         Self {
-            ingredients: ingredients.into_iter().collect::<HashSet<_>>(),
+            nom: modele.nom.clone(),
+            ingredients: modele.ingredients().collect(),
+            couches: modele.couches.clone(),
+            points: modele
+                .points
+                .unwrap_or(nombre as i32 * config.points_par_ingredient),
             creation,
             duree,
             expiration,
         }
     }
 
-    pub fn default_recipe() -> Self {
-        Recette::new(Instant::now())
+    pub fn get_nom(&self) -> &str {
+        &self.nom
+    }
+
+    pub fn get_points(&self) -> i32 {
+        self.points
+    }
+
+    pub fn get_couches(&self) -> &Vec<Vec<Ingredient>> {
+        &self.couches
     }
 
-    pub fn get_ingredients(&self) -> &HashSet<Ingredient> {
+    pub fn is_ordonnee(&self) -> bool {
+        self.couches.len() > 1
+    }
+
+    // nombre d'ingrédients du bas de l'assiette posés dans un ordre que la recette accepte,
+    // avec ce qu'il reste à poser de la couche en cours
+    fn empiler(&self, assiette: &Assiette) -> (usize, Contenu) {
+        let pile = assiette.get_ingredients();
+        let mut valides = 0;
+        for couche in &self.couches {
+            let mut reste = couche.iter().copied().collect::<Contenu>();
+            while let Some(ingredient) = pile.get(valides)
+                && reste.retirer(ingredient)
+            {
+                valides += 1;
+            }
+            if !reste.is_empty() {
+                return (valides, reste);
+            }
+        }
+        (valides, Contenu::default())
+    }
+
+    // l'assiette peut encore devenir cette recette en ajoutant des ingrédients
+    pub fn peut_completer(&self, assiette: &Assiette) -> bool {
+        self.empiler(assiette).0 == assiette.get_ingredients().len()
+    }
+
+    pub fn accepte(&self, assiette: &Assiette) -> bool {
+        self.peut_completer(assiette) && assiette.contenu() == self.ingredients
+    }
+
+    // ce qu'on peut poser maintenant sans casser l'ordre des couches
+    pub fn prochains(&self, assiette: &Assiette) -> Contenu {
+        self.empiler(assiette).1
+    }
+
+    // le premier ingrédient de l'assiette que la recette ne demande pas, ou pas à cet étage
+    pub fn intrus(&self, assiette: &Assiette) -> Option<Ingredient> {
+        let (valides, _) = self.empiler(assiette);
+        assiette.get_ingredients().get(valides).copied()
+    }
+
+    // empilée exactement dans l'ordre écrit dans le livre
+    pub fn is_ordre_exact(&self, assiette: &Assiette) -> bool {
+        self.is_ordonnee() && assiette.get_ingredients() == self.couches.concat()
+    }
+
+    pub fn get_ingredients(&self) -> &Contenu {
         &self.ingredients
     }
 
@@ -204,12 +603,12 @@ impl Recette {
         self.expiration <= now
     }
 
-    pub fn get_temps_restant(&self) -> Duration {
-        self.expiration - Instant::now()
+    pub fn get_temps_restant(&self, now: Instant) -> Duration {
+        self.expiration.saturating_duration_since(now)
     }
 
-    pub fn get_percent_left(&self) -> f32 {
-        self.get_temps_restant().as_secs_f32() / self.duree.as_secs_f32()
+    pub fn get_percent_left(&self, now: Instant) -> f32 {
+        self.get_temps_restant(now).as_secs_f32() / self.duree.as_secs_f32()
     }
 
     pub fn is_same(&self, other: &Recette) -> bool {
@@ -220,12 +619,178 @@ impl Recette {
 impl Display for Recette {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         let ingredients = self
-            .ingredients
+            .couches
             .iter()
-            .map(Ingredient::to_string)
+            .map(|couche| {
+                couche
+                    .iter()
+                    .map(Ingredient::to_string)
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            })
             .collect::<Vec<_>>()
-            .join(", ");
-        let temps = self.get_temps_restant().as_secs_f32();
-        write!(f, "{temps:.2}s, [{ingredients}]")
+            .join(" | ");
+        let duree = self.duree.as_secs_f32();
+        write!(f, "{} ({duree:.2}s), [{ingredients}]", self.nom)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PAIN: Ingredient = Ingredient {
+        type_ingredient: IngredientType::Pain,
+        etat: IngredientEtat::Normal,
+    };
+    const SALADE: Ingredient = Ingredient {
+        type_ingredient: IngredientType::Salade,
+        etat: IngredientEtat::Normal,
+    };
+    const TOMATE: Ingredient = Ingredient {
+        type_ingredient: IngredientType::Tomate,
+        etat: IngredientEtat::Normal,
+    };
+
+    fn contenu(ingredients: &[Ingredient]) -> Contenu {
+        ingredients.iter().copied().collect()
+    }
+
+    #[test]
+    fn le_contenu_compte_les_repetitions() {
+        let contenu = contenu(&[PAIN, SALADE, PAIN]);
+        assert_eq!(contenu.len(), 3);
+        assert_eq!(contenu.count(&PAIN), 2);
+        assert_eq!(contenu.count(&SALADE), 1);
+        assert_eq!(contenu.iter().collect::<Vec<_>>(), vec![PAIN, PAIN, SALADE]);
+    }
+
+    #[test]
+    fn deux_pains_ne_valent_pas_un() {
+        assert_ne!(contenu(&[PAIN, PAIN]), contenu(&[PAIN]));
+        assert!(contenu(&[PAIN]).is_subset(&contenu(&[PAIN, PAIN])));
+        assert!(!contenu(&[PAIN, PAIN]).is_subset(&contenu(&[PAIN, SALADE])));
+    }
+
+    #[test]
+    fn la_difference_garde_les_repetitions() {
+        let recette = contenu(&[PAIN, PAIN, SALADE]);
+        assert_eq!(
+            recette.difference(&contenu(&[PAIN])),
+            contenu(&[PAIN, SALADE])
+        );
+        assert_eq!(contenu(&[PAIN]).difference(&recette), Contenu::default());
+        assert_eq!(recette.ecart(&contenu(&[PAIN, SALADE, SALADE])), 2);
+    }
+
+    #[test]
+    fn retirer_enleve_un_seul_exemplaire() {
+        let mut contenu = contenu(&[PAIN, PAIN]);
+        assert!(contenu.retirer(&PAIN));
+        assert_eq!(contenu.count(&PAIN), 1);
+        assert!(contenu.retirer(&PAIN));
+        assert!(contenu.is_empty());
+        assert!(!contenu.retirer(&PAIN));
+    }
+
+    #[test]
+    fn le_contenu_d_une_assiette_garde_les_doublons() {
+        let mut assiette = Assiette::default();
+        assert!(assiette.ajouter(PAIN));
+        assert!(assiette.ajouter(PAIN));
+        assert_eq!(assiette.contenu(), contenu(&[PAIN, PAIN]));
+    }
+
+    fn recette(couches: &[&[Ingredient]]) -> Recette {
+        let couches = couches
+            .iter()
+            .map(|couche| couche.to_vec())
+            .collect::<Vec<_>>();
+        let creation = Instant::now();
+        let duree = Duration::from_secs(30);
+        Recette {
+            nom: "Test".to_string(),
+            ingredients: couches.iter().flatten().copied().collect(),
+            couches,
+            points: 0,
+            creation,
+            duree,
+            expiration: creation + duree,
+        }
+    }
+
+    fn assiette(ingredients: &[Ingredient]) -> Assiette {
+        let mut assiette = Assiette::default();
+        for &ingredient in ingredients {
+            assert!(assiette.ajouter(ingredient));
+        }
+        assiette
+    }
+
+    // pain | salade, tomate | pain
+    fn sandwich() -> Recette {
+        recette(&[&[PAIN], &[SALADE, TOMATE], &[PAIN]])
+    }
+
+    #[test]
+    fn une_pile_dans_l_ordre_du_livre_est_exacte() {
+        let plat = assiette(&[PAIN, SALADE, TOMATE, PAIN]);
+        assert!(sandwich().accepte(&plat));
+        assert!(sandwich().is_ordre_exact(&plat));
+        assert_eq!(sandwich().intrus(&plat), None);
+    }
+
+    #[test]
+    fn l_ordre_est_libre_dans_une_couche() {
+        let plat = assiette(&[PAIN, TOMATE, SALADE, PAIN]);
+        assert!(sandwich().accepte(&plat));
+        assert!(!sandwich().is_ordre_exact(&plat));
+    }
+
+    #[test]
+    fn une_couche_dans_le_mauvais_ordre_est_refusee() {
+        let plat = assiette(&[SALADE, PAIN, TOMATE, PAIN]);
+        assert_eq!(plat.contenu(), *sandwich().get_ingredients());
+        assert!(!sandwich().peut_completer(&plat));
+        assert!(!sandwich().accepte(&plat));
+        assert_eq!(sandwich().intrus(&plat), Some(SALADE));
+
+        // le pain du haut posé avant la garniture
+        let plat = assiette(&[PAIN, PAIN]);
+        assert!(!sandwich().peut_completer(&plat));
+        assert_eq!(sandwich().intrus(&plat), Some(PAIN));
+    }
+
+    #[test]
+    fn une_couche_incomplete_attend_la_suite() {
+        let plat = assiette(&[PAIN, SALADE]);
+        assert!(sandwich().peut_completer(&plat));
+        assert!(!sandwich().accepte(&plat));
+        assert_eq!(sandwich().prochains(&plat), contenu(&[TOMATE]));
+
+        // il ne manque que la couche du haut
+        let plat = assiette(&[PAIN, TOMATE, SALADE]);
+        assert!(sandwich().peut_completer(&plat));
+        assert!(!sandwich().accepte(&plat));
+        assert_eq!(sandwich().prochains(&plat), contenu(&[PAIN]));
+        assert_eq!(sandwich().intrus(&plat), None);
+    }
+
+    #[test]
+    fn une_recette_libre_compte_les_doublons() {
+        let recette = recette(&[&[PAIN, PAIN, SALADE]]);
+        assert!(!recette.is_ordonnee());
+
+        let plat = assiette(&[SALADE, PAIN]);
+        assert!(recette.peut_completer(&plat));
+        assert_eq!(recette.prochains(&plat), contenu(&[PAIN]));
+
+        let plat = assiette(&[PAIN, SALADE, PAIN]);
+        assert!(recette.accepte(&plat));
+        assert!(!recette.is_ordre_exact(&plat));
+
+        let plat = assiette(&[PAIN, PAIN, PAIN]);
+        assert!(!recette.peut_completer(&plat));
+        assert_eq!(recette.intrus(&plat), Some(PAIN));
     }
 }
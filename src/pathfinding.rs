@@ -0,0 +1,375 @@
+use std::{
+    cmp::Reverse,
+    collections::{BinaryHeap, HashMap, HashSet, VecDeque},
+};
+
+use crate::objets::{Case, Direction};
+
+pub fn is_walkable(case: &Case) -> bool {
+    *case == Case::Vide
+}
+
+pub fn neighbours(map: &[Vec<Case>], (x, y): (usize, usize)) -> Vec<(usize, usize)> {
+    let mut neighbours = Vec::new();
+    if x > 0 {
+        neighbours.push((x - 1, y));
+    }
+    if y > 0 {
+        neighbours.push((x, y - 1));
+    }
+    if x + 1 < map[y].len() {
+        neighbours.push((x + 1, y));
+    }
+    if y + 1 < map.len() {
+        neighbours.push((x, y + 1));
+    }
+    neighbours
+}
+
+// Chemin vers une case avec laquelle interagir (table, caisse, planche...)
+#[derive(Debug, PartialEq, Clone)]
+pub struct Route {
+    // du départ jusqu'à la case où se placer, départ inclus
+    pub path: Vec<(usize, usize)>,
+    pub target: (usize, usize),
+    // direction à regarder une fois au bout du chemin
+    pub facing: Direction,
+}
+
+impl Route {
+    pub fn distance(&self) -> usize {
+        self.path.len() - 1
+    }
+
+    pub fn is_arrived(&self) -> bool {
+        self.path.len() == 1
+    }
+
+    // direction du prochain pas, ou celle de la cible si on est déjà arrivé
+    pub fn next_direction(&self) -> Direction {
+        match self.path.get(..2) {
+            Some([from, to]) => Direction::between(*from, *to).unwrap_or(self.facing),
+            _ => self.facing,
+        }
+    }
+}
+
+// Distances depuis une origine vers toutes les cases praticables (parcours en largeur)
+#[derive(Debug, PartialEq, Clone)]
+pub struct DistanceMap {
+    origin: (usize, usize),
+    distances: Vec<Vec<usize>>,
+}
+
+impl DistanceMap {
+    pub fn new(map: &[Vec<Case>], origin: (usize, usize)) -> Self {
+        let mut distances = map
+            .iter()
+            .map(|row| vec![usize::MAX; row.len()])
+            .collect::<Vec<_>>();
+        distances[origin.1][origin.0] = 0;
+
+        let mut next_positions = VecDeque::from([origin]);
+        while let Some(pos) = next_positions.pop_front() {
+            let distance = distances[pos.1][pos.0];
+            for (x, y) in neighbours(map, pos) {
+                if distances[y][x] == usize::MAX && is_walkable(&map[y][x]) {
+                    distances[y][x] = distance + 1;
+                    next_positions.push_back((x, y));
+                }
+            }
+        }
+
+        Self { origin, distances }
+    }
+
+    pub fn get_origin(&self) -> (usize, usize) {
+        self.origin
+    }
+
+    pub fn distance(&self, (x, y): (usize, usize)) -> Option<usize> {
+        self.distances
+            .get(y)
+            .and_then(|row| row.get(x))
+            .copied()
+            .filter(|&distance| distance != usize::MAX)
+    }
+
+    pub fn is_reachable(&self, pos: (usize, usize)) -> bool {
+        self.distance(pos).is_some()
+    }
+
+    // remonte les distances décroissantes depuis l'arrivée
+    pub fn path_to(&self, map: &[Vec<Case>], pos: (usize, usize)) -> Option<Vec<(usize, usize)>> {
+        let mut distance = self.distance(pos)?;
+        let mut path = vec![pos];
+        let mut current = pos;
+        while distance > 0 {
+            current = neighbours(map, current)
+                .into_iter()
+                .find(|&next| self.distance(next) == Some(distance - 1))?;
+            distance -= 1;
+            path.push(current);
+        }
+        path.reverse();
+        Some(path)
+    }
+
+    // meilleure case praticable voisine de la cible
+    fn best_stand(&self, map: &[Vec<Case>], target: (usize, usize)) -> Option<(usize, usize)> {
+        neighbours(map, target)
+            .into_iter()
+            .filter(|&pos| self.is_reachable(pos))
+            .min_by_key(|&pos| self.distance(pos))
+    }
+
+    // nombre de pas pour venir interagir avec la cible
+    pub fn distance_to(&self, map: &[Vec<Case>], target: (usize, usize)) -> Option<usize> {
+        self.distance(self.best_stand(map, target)?)
+    }
+
+    pub fn route_to(&self, map: &[Vec<Case>], target: (usize, usize)) -> Option<Route> {
+        let stand = self.best_stand(map, target)?;
+        Some(Route {
+            path: self.path_to(map, stand)?,
+            target,
+            facing: Direction::between(stand, target)?,
+        })
+    }
+
+    // route vers la plus proche des cases qui vérifient le prédicat
+    pub fn nearest(&self, map: &[Vec<Case>], predicate: impl Fn(&Case) -> bool) -> Option<Route> {
+        self.cheapest(map, |case| predicate(case).then_some(0))
+    }
+
+    // comme nearest, mais chaque case ajoute son propre coût à la distance (None pour l'écarter)
+    pub fn cheapest(
+        &self,
+        map: &[Vec<Case>],
+        cout: impl Fn(&Case) -> Option<usize>,
+    ) -> Option<Route> {
+        let target = map
+            .iter()
+            .enumerate()
+            .flat_map(|(y, row)| {
+                row.iter()
+                    .enumerate()
+                    .filter_map(|(x, case)| Some(((x, y), cout(case)?)))
+                    .collect::<Vec<_>>()
+            })
+            .filter_map(|(target, cout)| {
+                Some((target, self.distance(self.best_stand(map, target)?)? + cout))
+            })
+            .min_by_key(|(_, cout)| *cout)?
+            .0;
+        self.route_to(map, target)
+    }
+}
+
+// Garde les cartes de distances déjà calculées tant que les cases praticables ne changent pas
+#[derive(Debug, Default, Clone)]
+pub struct Pathfinder {
+    walkable: Vec<Vec<bool>>,
+    cache: HashMap<(usize, usize), DistanceMap>,
+}
+
+impl Pathfinder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn invalidate(&mut self) {
+        self.walkable.clear();
+        self.cache.clear();
+    }
+
+    fn refresh(&mut self, map: &[Vec<Case>]) {
+        let same_layout = self.walkable.len() == map.len()
+            && self.walkable.iter().zip(map).all(|(walkable, row)| {
+                walkable.len() == row.len()
+                    && walkable
+                        .iter()
+                        .zip(row)
+                        .all(|(w, case)| *w == is_walkable(case))
+            });
+        if !same_layout {
+            self.walkable = map
+                .iter()
+                .map(|row| row.iter().map(is_walkable).collect())
+                .collect();
+            self.cache.clear();
+        }
+    }
+
+    pub fn distances_from(&mut self, map: &[Vec<Case>], origin: (usize, usize)) -> &DistanceMap {
+        self.refresh(map);
+        self.cache
+            .entry(origin)
+            .or_insert_with(|| DistanceMap::new(map, origin))
+    }
+
+    pub fn route(
+        &mut self,
+        map: &[Vec<Case>],
+        start: (usize, usize),
+        target: (usize, usize),
+    ) -> Option<Route> {
+        self.distances_from(map, start).route_to(map, target)
+    }
+
+    pub fn nearest(
+        &mut self,
+        map: &[Vec<Case>],
+        start: (usize, usize),
+        predicate: impl Fn(&Case) -> bool,
+    ) -> Option<Route> {
+        self.distances_from(map, start).nearest(map, predicate)
+    }
+}
+
+pub fn manhattan(a: (usize, usize), b: (usize, usize)) -> usize {
+    a.0.abs_diff(b.0) + a.1.abs_diff(b.1)
+}
+
+// A* entre deux cases praticables, en évitant en plus les cases bloquées (autres joueurs...)
+pub fn astar(
+    map: &[Vec<Case>],
+    start: (usize, usize),
+    goal: (usize, usize),
+    blocked: &HashSet<(usize, usize)>,
+) -> Option<Vec<(usize, usize)>> {
+    let mut came_from: HashMap<(usize, usize), (usize, usize)> = HashMap::new();
+    let mut costs: HashMap<(usize, usize), usize> = HashMap::from([(start, 0)]);
+    let mut open = BinaryHeap::from([Reverse((manhattan(start, goal), start))]);
+
+    while let Some(Reverse((_, pos))) = open.pop() {
+        if pos == goal {
+            let mut path = vec![pos];
+            while let Some(&previous) = came_from.get(path.last()?) {
+                path.push(previous);
+            }
+            path.reverse();
+            return Some(path);
+        }
+
+        let cost = costs[&pos] + 1;
+        for next in neighbours(map, pos) {
+            if !is_walkable(&map[next.1][next.0]) || (blocked.contains(&next) && next != goal) {
+                continue;
+            }
+            if costs.get(&next).is_none_or(|&known| cost < known) {
+                costs.insert(next, cost);
+                came_from.insert(next, pos);
+                open.push(Reverse((cost + manhattan(next, goal), next)));
+            }
+        }
+    }
+
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        map::parse_map,
+        objets::{Ingredient, IngredientType},
+    };
+
+    // un anneau de cases vides autour d'une table
+    const ANNEAU: &str = "#####\n#·  #\n# # #\n#   #\n#####";
+
+    fn carte(text: &str) -> Vec<Vec<Case>> {
+        parse_map(text).unwrap().map
+    }
+
+    fn is_chemin(map: &[Vec<Case>], path: &[(usize, usize)]) -> bool {
+        path.windows(2)
+            .all(|pas| manhattan(pas[0], pas[1]) == 1 && is_walkable(&map[pas[1].1][pas[1].0]))
+    }
+
+    #[test]
+    fn le_parcours_en_largeur_donne_les_distances() {
+        let map = carte(ANNEAU);
+        let distances = DistanceMap::new(&map, (1, 1));
+        assert_eq!(distances.distance((1, 1)), Some(0));
+        assert_eq!(distances.distance((3, 1)), Some(2));
+        assert_eq!(distances.distance((3, 3)), Some(4));
+        // ni les tables ni le dehors de la carte
+        assert_eq!(distances.distance((2, 2)), None);
+        assert_eq!(distances.distance((9, 9)), None);
+
+        let path = distances.path_to(&map, (3, 3)).unwrap();
+        assert_eq!(path.len(), 5);
+        assert_eq!(path.first(), Some(&(1, 1)));
+        assert_eq!(path.last(), Some(&(3, 3)));
+        assert!(is_chemin(&map, &path));
+    }
+
+    #[test]
+    fn une_case_fermee_est_inaccessible() {
+        let map = carte("######\n#·#  #\n######");
+        let distances = DistanceMap::new(&map, (1, 1));
+        assert!(!distances.is_reachable((3, 1)));
+        assert_eq!(distances.path_to(&map, (3, 1)), None);
+        assert_eq!(distances.route_to(&map, (4, 0)), None);
+    }
+
+    #[test]
+    fn la_route_s_arrete_devant_la_cible() {
+        let map = carte(ANNEAU);
+        let route = DistanceMap::new(&map, (1, 1))
+            .route_to(&map, (2, 2))
+            .unwrap();
+        assert_eq!(route.distance(), 1);
+        assert_eq!(route.target, (2, 2));
+        assert_eq!(
+            Direction::between(*route.path.last().unwrap(), (2, 2)),
+            Some(route.facing)
+        );
+    }
+
+    #[test]
+    fn le_cache_ne_change_qu_avec_les_cases_praticables() {
+        let mut map = carte(ANNEAU);
+        let mut pathfinder = Pathfinder::new();
+        pathfinder.distances_from(&map, (1, 1));
+        pathfinder.distances_from(&map, (3, 3));
+        assert_eq!(pathfinder.cache.len(), 2);
+
+        // un ingrédient posé sur la table ne change aucun chemin
+        map[2][2] = Case::Table(Some(Ingredient::new(IngredientType::Pain)));
+        pathfinder.distances_from(&map, (1, 1));
+        assert_eq!(pathfinder.cache.len(), 2);
+
+        // une table qui coupe l'anneau, si
+        map[1][2] = Case::Table(None);
+        let distances = pathfinder.distances_from(&map, (1, 1));
+        assert_eq!(distances.distance((3, 1)), Some(6));
+        assert_eq!(pathfinder.cache.len(), 1);
+    }
+
+    #[test]
+    fn astar_contourne_les_cases_bloquees() {
+        let map = carte(ANNEAU);
+        let direct = astar(&map, (1, 1), (3, 1), &HashSet::new()).unwrap();
+        assert_eq!(direct, vec![(1, 1), (2, 1), (3, 1)]);
+
+        let detour = astar(&map, (1, 1), (3, 1), &HashSet::from([(2, 1)])).unwrap();
+        assert_eq!(detour.len(), 7);
+        assert!(!detour.contains(&(2, 1)));
+        assert!(is_chemin(&map, &detour));
+
+        assert_eq!(
+            astar(&map, (1, 1), (3, 1), &HashSet::from([(2, 1), (1, 2)])),
+            None
+        );
+    }
+
+    #[test]
+    fn astar_peut_finir_sur_une_case_bloquee() {
+        let map = carte(ANNEAU);
+        let path = astar(&map, (1, 1), (3, 1), &HashSet::from([(3, 1)])).unwrap();
+        assert_eq!(path, vec![(1, 1), (2, 1), (3, 1)]);
+    }
+}
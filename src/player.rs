@@ -1,21 +1,27 @@
-use crate::objets::{Direction, Ingredient};
+use crate::objets::{Direction, Ingredient, Objet};
 
 #[derive(Debug, PartialEq, Clone)]
 pub struct Player {
+    id: usize,
     position: (usize, usize),
-    object_held: Option<Ingredient>,
+    object_held: Option<Objet>,
     facing: Direction,
 }
 
 impl Player {
-    pub fn new(position: (usize, usize)) -> Self {
+    pub fn new(id: usize, position: (usize, usize)) -> Self {
         Self {
+            id,
             position,
             object_held: None,
             facing: Direction::North,
         }
     }
 
+    pub fn get_id(&self) -> usize {
+        self.id
+    }
+
     pub fn get_pos(&self) -> (usize, usize) {
         self.position
     }
@@ -33,15 +39,19 @@ impl Player {
         self.facing = direction;
     }
 
-    pub fn get_object_held(&self) -> Option<Ingredient> {
+    pub fn get_object_held(&self) -> Option<Objet> {
         self.object_held
     }
 
-    pub fn take_object_held(&mut self) -> Option<Ingredient> {
+    pub fn get_ingredient_held(&self) -> Option<Ingredient> {
+        self.object_held.and_then(Objet::ingredient)
+    }
+
+    pub fn take_object_held(&mut self) -> Option<Objet> {
         self.object_held.take()
     }
 
-    pub fn set_object_held(&mut self, object: Option<Ingredient>) {
+    pub fn set_object_held(&mut self, object: Option<Objet>) {
         self.object_held = object
     }
 }
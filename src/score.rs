@@ -0,0 +1,155 @@
+use crate::{config::GameConfig, objets::Recette};
+
+// Détail du score d'une partie, poste par poste
+#[derive(Debug, Default, PartialEq, Clone)]
+pub struct Score {
+    // points de base des recettes livrées
+    pub recettes: i32,
+    // d'autant plus gros que la commande est servie tôt
+    pub pourboires: i32,
+    pub ordre: i32,
+    // ce que le multiplicateur de série ajoute aux recettes et pourboires
+    pub serie: i32,
+    // comptées en positif, retirées du total
+    pub expirations: i32,
+    pub poubelle: i32,
+    // livraisons à l'heure d'affilée, remises à zéro par une commande expirée
+    livraisons_d_affilee: u32,
+    meilleure_serie: u32,
+}
+
+impl Score {
+    pub fn total(&self) -> i32 {
+        self.recettes + self.pourboires + self.ordre + self.serie - self.expirations - self.poubelle
+    }
+
+    pub fn get_livraisons_d_affilee(&self) -> u32 {
+        self.livraisons_d_affilee
+    }
+
+    pub fn get_meilleure_serie(&self) -> u32 {
+        self.meilleure_serie
+    }
+
+    // appliqué à la prochaine livraison
+    pub fn multiplicateur(&self, config: &GameConfig) -> f32 {
+        (1. + config.bonus_serie * self.livraisons_d_affilee as f32).min(config.multiplicateur_max)
+    }
+
+    // points gagnés par une recette livrée avec `percent_left` de son temps restant
+    pub fn livrer(
+        &mut self,
+        recette: &Recette,
+        percent_left: f32,
+        ordre_exact: bool,
+        config: &GameConfig,
+    ) {
+        let base = recette.get_points();
+        let pourboire = (config.pourboire_max as f32 * percent_left.clamp(0., 1.)).round() as i32;
+        let serie = ((base + pourboire) as f32 * (self.multiplicateur(config) - 1.)).round() as i32;
+        let ordre = if ordre_exact { config.bonus_ordre } else { 0 };
+
+        self.recettes += base;
+        self.pourboires += pourboire;
+        self.serie += serie;
+        self.ordre += ordre;
+        self.livraisons_d_affilee += 1;
+        self.meilleure_serie = self.meilleure_serie.max(self.livraisons_d_affilee);
+    }
+
+    // une pénalité, jamais un gain : `penalite` ne descend pas sous zéro
+    pub fn expirer(&mut self, recette: &Recette, config: &GameConfig) {
+        self.expirations += config.penalite(recette.get_ingredients().len());
+        self.livraisons_d_affilee = 0;
+    }
+
+    pub fn jeter(&mut self, ingredients: usize, config: &GameConfig) {
+        self.poubelle += ingredients as i32 * config.penalite_poubelle;
+    }
+}
+
+impl std::fmt::Display for Score {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "Recettes : {:+}", self.recettes)?;
+        writeln!(f, "Pourboires : {:+}", self.pourboires)?;
+        writeln!(f, "Ordre exact : {:+}", self.ordre)?;
+        writeln!(
+            f,
+            "Série : {:+} (meilleure : {})",
+            self.serie, self.meilleure_serie
+        )?;
+        writeln!(f, "Expirations : {:+}", -self.expirations)?;
+        writeln!(f, "Poubelle : {:+}", -self.poubelle)?;
+        write!(f, "Total : {}", self.total())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Instant;
+
+    use rand::{SeedableRng, rngs::StdRng};
+
+    use super::*;
+
+    // une configuration dont le livre ne contient qu'une recette
+    fn config(ingredients: &str) -> GameConfig {
+        GameConfig {
+            livre: format!("[Test]\ningredients = {ingredients}")
+                .parse()
+                .unwrap(),
+            ..GameConfig::default()
+        }
+    }
+
+    fn commande(config: &GameConfig) -> Recette {
+        Recette::new(Instant::now(), &mut StdRng::seed_from_u64(0), config)
+    }
+
+    #[test]
+    fn une_grosse_recette_expiree_ne_rapporte_rien() {
+        let config = GameConfig {
+            penalite_expiration: 2,
+            ..config("pain, salade, tomate, oignon, viande")
+        };
+        let mut score = Score::default();
+        score.expirer(&commande(&config), &config);
+        assert_eq!(score.expirations, 0);
+        assert_eq!(score.total(), 0);
+    }
+
+    #[test]
+    fn une_penalite_negative_ne_rapporte_rien() {
+        let config = GameConfig {
+            penalite_expiration: -5,
+            ..config("pain, salade")
+        };
+        let mut score = Score::default();
+        score.expirer(&commande(&config), &config);
+        assert_eq!(score.total(), 0);
+    }
+
+    #[test]
+    fn une_petite_recette_expiree_coute_des_points() {
+        let config = config("pain, salade");
+        let mut score = Score::default();
+        score.expirer(&commande(&config), &config);
+        assert_eq!(score.total(), -(config.penalite_expiration - 2));
+    }
+
+    #[test]
+    fn une_expiration_remet_la_serie_a_zero() {
+        let config = config("pain, salade");
+        let recette = commande(&config);
+        let mut score = Score::default();
+        score.livrer(&recette, 1., false, &config);
+        score.livrer(&recette, 1., false, &config);
+        assert_eq!(score.get_livraisons_d_affilee(), 2);
+        assert!(score.multiplicateur(&config) > 1.);
+
+        score.expirer(&recette, &config);
+        assert_eq!(score.get_livraisons_d_affilee(), 0);
+        assert_eq!(score.get_meilleure_serie(), 2);
+        assert_eq!(score.multiplicateur(&config), 1.);
+    }
+}
@@ -0,0 +1,305 @@
+use std::time::Duration;
+
+use crate::{clock::Clock, game::Game, score::Score, strategy::Strategy};
+
+// pas de temps virtuel entre deux ticks, à peu près une frame de l'interface
+pub const SIMULATION_STEP: Duration = Duration::from_millis(16);
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct SimulationResult {
+    pub seed: u64,
+    pub chefs: usize,
+    pub duree: Duration,
+    // plus court que la durée prévue quand la partie est perdue avant la fin
+    pub duree_jouee: Duration,
+    pub score: i32,
+    pub detail: Score,
+    pub etoiles: usize,
+    pub perdu: bool,
+    pub recettes_livrees: usize,
+    pub recettes_expirees: usize,
+    pub premiere_livraison: Option<Duration>,
+}
+
+impl SimulationResult {
+    pub fn from_game(game: &Game) -> Self {
+        Self {
+            seed: game.get_seed(),
+            chefs: game.get_players().len(),
+            duree: game.get_config().duree_partie,
+            duree_jouee: game
+                .now()
+                .saturating_duration_since(*game.get_start_instant())
+                .min(game.get_config().duree_partie),
+            score: game.get_score(),
+            detail: game.get_detail_score().clone(),
+            etoiles: game.get_etoiles(),
+            perdu: game.is_perdu(),
+            recettes_livrees: game.get_recettes_livrees(),
+            recettes_expirees: game.get_recettes_expirees(),
+            premiere_livraison: game.get_premiere_livraison(),
+        }
+    }
+}
+
+// Une ligne par partie
+impl std::fmt::Display for SimulationResult {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let fin = if self.perdu {
+            format!(", perdue après {:.0}s", self.duree_jouee.as_secs_f32())
+        } else {
+            String::new()
+        };
+        write!(
+            f,
+            "Graine {} ({} chef(s), {:.0}s) : score {}, {}/3 étoiles{fin}, {} livrées, {} expirées, première livraison ",
+            self.seed,
+            self.chefs,
+            self.duree.as_secs_f32(),
+            self.score,
+            self.etoiles,
+            self.recettes_livrees,
+            self.recettes_expirees
+        )?;
+        match self.premiere_livraison {
+            Some(duree) => write!(f, "{:.2}s", duree.as_secs_f32()),
+            None => write!(f, "aucune"),
+        }
+    }
+}
+
+// Joue une partie entière sans interface, en temps virtuel
+pub fn simulate(game: Game, strategy: &mut dyn Strategy) -> SimulationResult {
+    let mut game = game.with_clock(Clock::manual());
+    let robot_cooldown = game.get_config().robot_cooldown;
+    let mut next_robot = game.now();
+
+    while !game.is_finished() {
+        let now = game.now();
+        if next_robot <= now {
+            for id in 0..game.get_players().len() {
+                // une action impossible est simplement perdue, comme un tour passé
+                let _ = game.robot(id, strategy);
+            }
+            next_robot = now + robot_cooldown;
+        }
+        game.tick();
+        game.advance_clock(SIMULATION_STEP);
+    }
+
+    SimulationResult::from_game(&game)
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct Summary {
+    pub parties: usize,
+    pub chefs: usize,
+    pub score_moyen: f32,
+    pub score_median: f32,
+    pub score_min: i32,
+    pub score_max: i32,
+    // chaque poste du score, en moyenne par partie
+    pub recettes_moyen: f32,
+    pub pourboires_moyen: f32,
+    pub ordre_moyen: f32,
+    pub serie_moyen: f32,
+    pub expirations_moyen: f32,
+    pub poubelle_moyen: f32,
+    pub meilleure_serie_moyenne: f32,
+    // nombre de parties à 0, 1, 2 et 3 étoiles
+    pub etoiles: [usize; 4],
+    pub parties_perdues: usize,
+    pub livrees_moyen: f32,
+    pub expirees_moyen: f32,
+    pub premiere_livraison_moyenne: Option<Duration>,
+    pub parties_sans_livraison: usize,
+    // recettes livrées par minute réellement jouée
+    pub debit: f32,
+}
+
+impl Summary {
+    pub fn new(results: &[SimulationResult]) -> Option<Self> {
+        if results.is_empty() {
+            return None;
+        }
+
+        let parties = results.len();
+        let mut scores = results.iter().map(|r| r.score).collect::<Vec<_>>();
+        scores.sort();
+        let score_median = if parties.is_multiple_of(2) {
+            (scores[parties / 2 - 1] + scores[parties / 2]) as f32 / 2.
+        } else {
+            scores[parties / 2] as f32
+        };
+
+        let premieres_livraisons = results
+            .iter()
+            .filter_map(|r| r.premiere_livraison)
+            .collect::<Vec<_>>();
+        let premiere_livraison_moyenne = if premieres_livraisons.is_empty() {
+            None
+        } else {
+            Some(premieres_livraisons.iter().sum::<Duration>() / premieres_livraisons.len() as u32)
+        };
+
+        let minutes = results
+            .iter()
+            .map(|r| r.duree_jouee)
+            .sum::<Duration>()
+            .as_secs_f32()
+            / 60.;
+        let livrees = results.iter().map(|r| r.recettes_livrees).sum::<usize>();
+        let moyenne = |poste: fn(&Score) -> i32| {
+            results.iter().map(|r| poste(&r.detail)).sum::<i32>() as f32 / parties as f32
+        };
+
+        Some(Self {
+            parties,
+            chefs: results.iter().map(|r| r.chefs).max().unwrap_or_default(),
+            score_moyen: scores.iter().sum::<i32>() as f32 / parties as f32,
+            score_median,
+            score_min: scores[0],
+            score_max: scores[parties - 1],
+            recettes_moyen: moyenne(|score| score.recettes),
+            pourboires_moyen: moyenne(|score| score.pourboires),
+            ordre_moyen: moyenne(|score| score.ordre),
+            serie_moyen: moyenne(|score| score.serie),
+            expirations_moyen: moyenne(|score| score.expirations),
+            poubelle_moyen: moyenne(|score| score.poubelle),
+            meilleure_serie_moyenne: moyenne(|score| score.get_meilleure_serie() as i32),
+            etoiles: results.iter().fold([0; 4], |mut etoiles, r| {
+                etoiles[r.etoiles.min(3)] += 1;
+                etoiles
+            }),
+            parties_perdues: results.iter().filter(|r| r.perdu).count(),
+            livrees_moyen: livrees as f32 / parties as f32,
+            expirees_moyen: results.iter().map(|r| r.recettes_expirees).sum::<usize>() as f32
+                / parties as f32,
+            premiere_livraison_moyenne,
+            parties_sans_livraison: parties - premieres_livraisons.len(),
+            debit: if minutes > 0. {
+                livrees as f32 / minutes
+            } else {
+                0.
+            },
+        })
+    }
+}
+
+impl std::fmt::Display for Summary {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(
+            f,
+            "Parties jouées : {} ({} chef(s))",
+            self.parties, self.chefs
+        )?;
+        writeln!(
+            f,
+            "Score : moyenne {:.2}, médiane {:.1}, min {}, max {}",
+            self.score_moyen, self.score_median, self.score_min, self.score_max
+        )?;
+        writeln!(
+            f,
+            "Détail (moyenne) : recettes {:+.2}, pourboires {:+.2}, ordre {:+.2}, série {:+.2}, expirations {:+.2}, poubelle {:+.2}",
+            self.recettes_moyen,
+            self.pourboires_moyen,
+            self.ordre_moyen,
+            self.serie_moyen,
+            -self.expirations_moyen,
+            -self.poubelle_moyen
+        )?;
+        writeln!(
+            f,
+            "Meilleure série (moyenne) : {:.2} livraisons d'affilée",
+            self.meilleure_serie_moyenne
+        )?;
+        writeln!(
+            f,
+            "Étoiles : 0★ {}, 1★ {}, 2★ {}, 3★ {} ({} parties perdues)",
+            self.etoiles[0],
+            self.etoiles[1],
+            self.etoiles[2],
+            self.etoiles[3],
+            self.parties_perdues
+        )?;
+        writeln!(
+            f,
+            "Recettes livrées (moyenne) : {:.2}, soit {:.2} par minute",
+            self.livrees_moyen, self.debit
+        )?;
+        writeln!(
+            f,
+            "Recettes expirées (moyenne) : {:.2}",
+            self.expirees_moyen
+        )?;
+        match self.premiere_livraison_moyenne {
+            Some(duree) => writeln!(
+                f,
+                "Première livraison (moyenne) : {:.2}s ({} parties sans livraison)",
+                duree.as_secs_f32(),
+                self.parties_sans_livraison
+            ),
+            None => writeln!(f, "Première livraison : aucune"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{config::GameConfig, game::RobotAction};
+
+    // un chef qui regarde les commandes expirer
+    struct Immobile;
+
+    impl Strategy for Immobile {
+        fn next_action(&mut self, _: &Game, _: usize) -> RobotAction {
+            RobotAction::None
+        }
+    }
+
+    fn resultat(recettes_livrees: usize, duree_jouee: Duration) -> SimulationResult {
+        let duree = Duration::from_secs(120);
+        SimulationResult {
+            seed: 0,
+            chefs: 1,
+            duree,
+            duree_jouee,
+            score: 0,
+            detail: Score::default(),
+            etoiles: 0,
+            perdu: duree_jouee < duree,
+            recettes_livrees,
+            recettes_expirees: 0,
+            premiere_livraison: None,
+        }
+    }
+
+    #[test]
+    fn une_partie_perdue_s_arrete_avant_la_fin() {
+        let config = GameConfig {
+            expirations_max: Some(1),
+            ..GameConfig::default()
+        };
+        let result = simulate(Game::new(config).unwrap().with_seed(0), &mut Immobile);
+        assert!(result.perdu);
+        assert!(result.duree_jouee < result.duree);
+
+        let result = simulate(Game::default().with_seed(0), &mut Immobile);
+        assert!(!result.perdu);
+        assert_eq!(result.duree_jouee, result.duree);
+    }
+
+    #[test]
+    fn le_debit_ne_compte_que_le_temps_joue() {
+        // six livraisons en deux minutes, puis trois en une minute avant de perdre
+        let summary = Summary::new(&[
+            resultat(6, Duration::from_secs(120)),
+            resultat(3, Duration::from_secs(60)),
+        ])
+        .unwrap();
+        assert_eq!(summary.debit, 3.);
+        assert_eq!(summary.parties_perdues, 1);
+        assert_eq!(summary.livrees_moyen, 4.5);
+    }
+}
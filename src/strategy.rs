@@ -0,0 +1,401 @@
+use std::collections::HashSet;
+
+use crate::{
+    coordination::Coordinateur,
+    game::{Game, RobotAction},
+    objets::{
+        Assiette, Case, Contenu, Decoupe, Direction, Ingredient, IngredientEtat, Lavage, Objet,
+        Recette,
+    },
+    pathfinding::{Pathfinder, Route, astar, is_walkable, manhattan, neighbours},
+};
+
+// en dessous, on jette ce qui ne sert pas tout de suite plutôt que d'encombrer les tables
+const TABLES_LIBRES_MIN: usize = 3;
+
+// Un planificateur : observe la partie et choisit la prochaine action du chef `id`
+pub trait Strategy {
+    fn next_action(&mut self, game: &Game, id: usize) -> RobotAction;
+}
+
+// un seul chef suit le robot d'origine, plusieurs se coordonnent
+pub fn strategie_par_defaut(chefs: usize) -> Box<dyn Strategy> {
+    if chefs > 1 {
+        Box::new(Coordinateur::new())
+    } else {
+        Box::new(DefaultStrategy::new())
+    }
+}
+
+// Le robot d'origine : prépare la recette la plus proche de l'assiette en cours
+#[derive(Debug, Default, Clone)]
+pub struct DefaultStrategy {
+    pathfinder: Pathfinder,
+}
+
+impl DefaultStrategy {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl Strategy for DefaultStrategy {
+    fn next_action(&mut self, game: &Game, id: usize) -> RobotAction {
+        self.determine_action(game, id)
+    }
+}
+
+impl DefaultStrategy {
+    fn determine_action(&mut self, game: &Game, id: usize) -> RobotAction {
+        let objectives = self.determine_objectives(game, id);
+        let player = game.get_player(id);
+        let distances = self
+            .pathfinder
+            .distances_from(game.get_map(), player.get_pos());
+
+        for objective_level in objectives {
+            // parmis un niveau d'objectif, choisir celui le plus proche en comptant la découpe restante
+            let route = distances.cheapest(game.get_map(), |case| {
+                objective_level
+                    .iter()
+                    .any(|objective| correspond(objective, case))
+                    .then(|| coups_restants(game, case))
+            });
+            let Some(route) = route else {
+                continue;
+            };
+
+            if !route.is_arrived() {
+                return contourner(game, id, &route, || s_ecarter(game, id));
+            }
+            let direction = route.next_direction();
+            if player.get_facing() != direction {
+                return RobotAction::Deplacer(direction);
+            }
+
+            if player.get_object_held().is_none() {
+                // attend que la cuisson se termine
+                if let (_, Case::POELE(Some(cuisson))) = game.get_facing(id)
+                    && !cuisson.is_pret()
+                {
+                    return RobotAction::None;
+                }
+                // un ingrédient en trop se retire sans emporter toute l'assiette
+                if let (_, Case::ASSIETTE(Some(assiette))) = game.get_facing(id)
+                    && let Some(intrus) = ingredient_en_trop(game, &assiette)
+                {
+                    return RobotAction::Retirer(intrus);
+                }
+                return RobotAction::Pickup;
+            } else {
+                return RobotAction::Deposit;
+            }
+        }
+
+        s_ecarter(game, id)
+    }
+
+    fn determine_objectives(&self, game: &Game, id: usize) -> Vec<Vec<Case>> {
+        let mut objectives = self.objectifs_recette(game, id);
+        // quand il n'y a rien d'autre à faire, la vaisselle
+        if game.get_player(id).get_object_held().is_none() {
+            objectives.push(vec![Case::EVIER(Some(Lavage::default()))]);
+            objectives.push(vec![Case::RETOUR(1)]);
+        }
+        objectives
+    }
+
+    fn objectifs_recette(&self, game: &Game, id: usize) -> Vec<Vec<Case>> {
+        let poeles = game
+            .get_map()
+            .iter()
+            .flatten()
+            .filter_map(|case| match case {
+                Case::POELE(Some(cuisson)) => Some(*cuisson),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        // une poêle brûlée ne sert plus à rien tant qu'on ne la vide pas
+        if game.get_player(id).get_object_held().is_none()
+            && let Some(brulee) = poeles
+                .iter()
+                .find(|cuisson| cuisson.ingredient.etat == IngredientEtat::Brule)
+        {
+            return vec![vec![Case::POELE(Some(*brulee))]];
+        }
+
+        // une assiette en main se sert, se lave ou retourne sur un emplacement
+        let en_main = game.get_player(id).get_object_held();
+        if let Some(Objet::Assiette(assiette)) = en_main {
+            return vec![vec![destination_assiette(game, &assiette)]];
+        }
+        let Some((_, assiette)) = assiette_de_travail(game) else {
+            // plus d'assiette propre : on se débarrasse de ce qu'on tient
+            if let Some(ingredient) = game.get_player(id).get_ingredient_held() {
+                return vec![vec![debarras(game, &ingredient)]];
+            }
+            return vec![];
+        };
+        let contenu = assiette.contenu();
+        let Some(recette) = recette_proche(game, &contenu) else {
+            return vec![];
+        };
+        // on compte les ingrédients : il peut en manquer un exemplaire qu'on a déjà posé
+        let manquants = recette.get_ingredients().difference(&contenu);
+        // une recette ordonnée ne prend que les ingrédients de la couche en cours
+        let mut recette_priv_assiette = recette.prochains(&assiette);
+
+        // assiette prête à servir ou à corriger : on y va les mains libres
+        if !recette.peut_completer(&assiette) || manquants.is_empty() {
+            if let Some(ingredient) = game.get_player(id).get_ingredient_held() {
+                return vec![vec![debarras(game, &ingredient)]];
+            }
+            return vec![vec![Case::ASSIETTE(Some(assiette))]];
+        }
+
+        if let Some(held_ingredient) = game.get_player(id).get_ingredient_held() {
+            if recette_priv_assiette.contains(&held_ingredient) {
+                return vec![vec![Case::ASSIETTE(Some(assiette))]];
+            } else if (held_ingredient.se_cuit() || held_ingredient.se_coupe())
+                && manquants
+                    .iter()
+                    .any(|ingr| held_ingredient.peut_devenir(&ingr))
+            {
+                if held_ingredient.se_cuit() {
+                    return vec![vec![Case::POELE(None)]];
+                }
+                return vec![vec![Case::COUPER(None)]];
+            } else {
+                return vec![vec![debarras(game, &held_ingredient)]];
+            }
+        }
+
+        // ce que les autres chefs ont en main est déjà en route, inutile d'aller le chercher
+        for en_main in game
+            .get_players()
+            .iter()
+            .filter(|player| player.get_id() != id)
+            .filter_map(|player| player.get_ingredient_held())
+        {
+            let voulu = recette_priv_assiette
+                .iter()
+                .find(|voulu| en_main.peut_devenir(voulu));
+            if let Some(voulu) = voulu {
+                recette_priv_assiette.retirer(&voulu);
+            }
+        }
+        let mut recette_priv_assiette_vec = recette_priv_assiette.iter().collect::<Vec<_>>();
+        if recette_priv_assiette_vec.is_empty() {
+            return vec![];
+        }
+
+        // choisit l'ingredient qui apparait le plus dans les recettes d'apres (au cas où la recette actuelle se termine)
+        recette_priv_assiette_vec.sort_by(|ingr1, ingr2| {
+            let ingr1_count = game
+                .get_recettes()
+                .iter()
+                .filter(|recette| recette.get_ingredients().contains(ingr1))
+                .count();
+            let ingr2_count = game
+                .get_recettes()
+                .iter()
+                .filter(|recette| recette.get_ingredients().contains(ingr2))
+                .count();
+            // count par ordre décroissant et ingrédients par ordre croissant
+            ingr2_count.cmp(&ingr1_count).then(ingr1.cmp(ingr2))
+        });
+        // ce qui cuit déjà attend la fin de la cuisson, on s'occupe du reste en attendant
+        let en_cuisson = |ingr: &Ingredient| {
+            poeles
+                .iter()
+                .any(|cuisson| cuisson.ingredient.peut_devenir(ingr))
+        };
+        recette_priv_assiette_vec.sort_by_key(en_cuisson);
+
+        let next_ingredient = recette_priv_assiette_vec.first().unwrap();
+        let pret = Ingredient::pret(next_ingredient.type_ingredient);
+        if let Some(cuisson) = poeles
+            .iter()
+            .find(|cuisson| cuisson.ingredient.peut_devenir(&pret))
+        {
+            return vec![vec![Case::Table(Some(pret)), Case::POELE(Some(*cuisson))]];
+        }
+        let cru = Ingredient::new(next_ingredient.type_ingredient);
+        vec![
+            // priorité à lui
+            vec![Case::Table(Some(pret))],
+            vec![
+                // sinon le plus proche d'eux, une planche déjà entamée comprise
+                Case::COUPER(Some(Decoupe::new(cru))),
+                Case::Table(Some(cru)),
+                Case::Ingredient(next_ingredient.type_ingredient),
+            ],
+        ]
+    }
+}
+
+// deux poêles correspondent si elles contiennent la même chose, quel que soit le début de cuisson
+fn correspond(objective: &Case, case: &Case) -> bool {
+    match (objective, case) {
+        (Case::POELE(Some(a)), Case::POELE(Some(b))) => a.ingredient == b.ingredient,
+        (Case::COUPER(Some(a)), Case::COUPER(Some(b))) => {
+            a.ingredient.type_ingredient == b.ingredient.type_ingredient
+        }
+        (Case::EVIER(Some(_)), Case::EVIER(Some(_))) => true,
+        (Case::RETOUR(_), Case::RETOUR(sales)) => *sales > 0,
+        _ => objective == case,
+    }
+}
+
+// la commande la plus proche de ce contenu d'assiette
+pub(crate) fn recette_proche<'a>(game: &'a Game, contenu: &Contenu) -> Option<&'a Recette> {
+    game.get_recettes()
+        .iter()
+        .min_by_key(|recette| recette.get_ingredients().ecart(contenu))
+}
+
+fn a_une_poubelle(game: &Game) -> bool {
+    game.get_map()
+        .iter()
+        .flatten()
+        .any(|case| *case == Case::POUBELLE)
+}
+
+// où se débarrasser d'un ingrédient : sur une table s'il peut encore servir,
+// à la poubelle s'il est inutile ou que les tables libres se font rares
+pub(crate) fn debarras(game: &Game, ingredient: &Ingredient) -> Case {
+    let utile = game
+        .get_recettes()
+        .iter()
+        .flat_map(|recette| recette.get_ingredients().iter())
+        .any(|voulu| ingredient.peut_devenir(&voulu));
+    let tables_libres = game
+        .get_map()
+        .iter()
+        .flatten()
+        .filter(|case| **case == Case::Table(None))
+        .count();
+    if a_une_poubelle(game) && (!utile || tables_libres < TABLES_LIBRES_MIN) {
+        Case::POUBELLE
+    } else {
+        Case::Table(None)
+    }
+}
+
+// le premier ingrédient de l'assiette que sa recette la plus proche ne demande pas,
+// pas autant de fois ou pas à cet étage
+pub(crate) fn ingredient_en_trop(game: &Game, assiette: &Assiette) -> Option<Ingredient> {
+    recette_proche(game, &assiette.contenu())?.intrus(assiette)
+}
+
+// où porter une assiette qu'on a en main : à la passe, à la plonge ou sur un emplacement
+pub(crate) fn destination_assiette(game: &Game, assiette: &Assiette) -> Case {
+    let contenu = assiette.contenu();
+    match recette_proche(game, &contenu) {
+        _ if assiette.sale => Case::EVIER(None),
+        Some(recette) if recette.accepte(assiette) => Case::PASSE,
+        Some(recette) if !contenu.is_subset(recette.get_ingredients()) => {
+            if a_une_poubelle(game) {
+                Case::POUBELLE
+            } else {
+                Case::EVIER(None)
+            }
+        }
+        _ => Case::ASSIETTE(None),
+    }
+}
+
+// l'assiette propre sur laquelle on dresse : celle qui est la plus proche d'une commande
+pub(crate) fn assiette_de_travail(game: &Game) -> Option<((usize, usize), Assiette)> {
+    game.get_assiettes()
+        .into_iter()
+        .filter(|(_, assiette)| !assiette.sale)
+        .min_by_key(|(pos, assiette)| {
+            let contenu = assiette.contenu();
+            let ecart = recette_proche(game, &contenu).map_or(usize::MAX, |recette| {
+                recette.get_ingredients().ecart(&contenu)
+            });
+            (ecart, *pos)
+        })
+}
+
+// coups de couteau qu'il faudra encore donner à ce qu'on trouve sur la case
+fn coups_restants(game: &Game, case: &Case) -> usize {
+    let config = game.get_config();
+    match case {
+        Case::COUPER(Some(decoupe)) => decoupe.coups_restants(config) as usize,
+        Case::Table(Some(ingredient)) if ingredient.se_coupe() => config.coups_decoupe as usize,
+        Case::Ingredient(type_ingredient) if Ingredient::new(*type_ingredient).se_coupe() => {
+            config.coups_decoupe as usize
+        }
+        _ => 0,
+    }
+}
+
+fn autres_chefs(game: &Game, id: usize) -> HashSet<(usize, usize)> {
+    game.get_players()
+        .iter()
+        .filter(|player| player.get_id() != id)
+        .map(|player| player.get_pos())
+        .collect()
+}
+
+// contourne les chefs qui barrent la route, sinon attend ou cède le passage aux chefs prioritaires
+pub(crate) fn contourner(
+    game: &Game,
+    id: usize,
+    route: &Route,
+    ceder: impl FnOnce() -> RobotAction,
+) -> RobotAction {
+    let next = route.path[1];
+    if !game.is_occupied(next) {
+        return RobotAction::Deplacer(route.next_direction());
+    }
+
+    let autres = autres_chefs(game, id);
+    let start = route.path[0];
+    let stand = *route.path.last().unwrap();
+    if let Some(path) = astar(game.get_map(), start, stand, &autres)
+        && let Some(&step) = path.get(1)
+        && !autres.contains(&step)
+        && let Some(direction) = Direction::between(start, step)
+    {
+        return RobotAction::Deplacer(direction);
+    }
+
+    let bloqueur = game
+        .get_players()
+        .iter()
+        .find(|player| player.get_pos() == next)
+        .map_or(usize::MAX, |player| player.get_id());
+    if bloqueur < id {
+        ceder()
+    } else {
+        RobotAction::None
+    }
+}
+
+// libère le passage : s'éloigne d'un pas des chefs voisins
+pub(crate) fn s_ecarter(game: &Game, id: usize) -> RobotAction {
+    let map = game.get_map();
+    let pos = game.get_player(id).get_pos();
+    let autres = autres_chefs(game, id);
+    let eloignement = |pos: (usize, usize)| {
+        autres
+            .iter()
+            .map(|&autre| manhattan(pos, autre))
+            .min()
+            .unwrap_or(usize::MAX)
+    };
+    if eloignement(pos) > 1 {
+        return RobotAction::None;
+    }
+
+    neighbours(map, pos)
+        .into_iter()
+        .filter(|&(x, y)| is_walkable(&map[y][x]) && !autres.contains(&(x, y)))
+        .filter(|&next| eloignement(next) > eloignement(pos))
+        .max_by_key(|&next| eloignement(next))
+        .and_then(|next| Direction::between(pos, next))
+        .map_or(RobotAction::None, RobotAction::Deplacer)
+}